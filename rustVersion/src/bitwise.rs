@@ -0,0 +1,56 @@
+use crate::error::*;
+use crate::stack::*;
+use crate::stackvalues::*;
+
+//the bitwise words, they work on the two's complement bits of the 64 bit integers
+
+fn bits(stack: &mut Stack, op: &str, function: fn(i64, i64) -> i64) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Int(function(x, y))),
+        (left, right) => Err(Error::type_mismatch(op, "two integers", &[&left, &right])),
+    })
+}
+
+pub fn band(stack: &mut Stack) -> Result<(), Error> {
+    bits(stack, "band", |x, y| x & y)
+}
+
+pub fn bor(stack: &mut Stack) -> Result<(), Error> {
+    bits(stack, "bor", |x, y| x | y)
+}
+
+pub fn bxor(stack: &mut Stack) -> Result<(), Error> {
+    bits(stack, "bxor", |x, y| x ^ y)
+}
+
+pub fn bnot(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => Ok(StackValue::Int(!x)),
+        value => Err(Error::type_mismatch("bnot", "an integer", &[&value])),
+    })
+}
+
+//( x n -- shifted ), the bits shifted out are lost and shr keeps the sign
+fn shift(stack: &mut Stack, op: &str, function: fn(i64, u32) -> i64) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Int(x), StackValue::Int(n)) if (0..64).contains(&n) => Ok(StackValue::Int(function(x, n as u32))),
+        (StackValue::Int(_), StackValue::Int(n)) => Err(Error::invalid(&format!("Can't shift by {} bits, it has to be from 0 to 63", n))),
+        (left, right) => Err(Error::type_mismatch(op, "two integers", &[&left, &right])),
+    })
+}
+
+pub fn shl(stack: &mut Stack) -> Result<(), Error> {
+    shift(stack, "shl", |x, n| x << n)
+}
+
+pub fn shr(stack: &mut Stack) -> Result<(), Error> {
+    shift(stack, "shr", |x, n| x >> n)
+}
+
+//the number of bits that are set
+pub fn popcount(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => Ok(StackValue::Int(x.count_ones() as i64)),
+        value => Err(Error::type_mismatch("popcount", "an integer", &[&value])),
+    })
+}
@@ -0,0 +1,407 @@
+use std::rc::Rc;
+
+use crate::bitwise;
+use crate::error::*;
+use crate::list::List;
+use crate::maps;
+use crate::math;
+use crate::numeric::*;
+use crate::stack::*;
+use crate::stackvalues::*;
+use crate::strings;
+
+//every built-in word takes the stack and changes it
+pub type Builtin = fn(&mut Stack) -> Result<(), Error>;
+
+//a word the program embedding the interpreter registered, it can be a closure so it may hold on to its own state
+pub type NativeWord = Rc<dyn Fn(&mut Stack) -> Result<(), Error>>;
+
+//the built-in words, the compiled code calls them by their index in this table
+pub const BUILTINS: &[(&str, Builtin)] = &[
+    ("dup", Stack::dup),
+    ("swap", Stack::swap),
+    ("pop", pop),
+    ("over", Stack::over),
+    ("rot", Stack::rot),
+    ("-rot", Stack::minus_rot),
+    ("nip", Stack::nip),
+    ("tuck", Stack::tuck),
+    ("pick", Stack::pick),
+    ("roll", Stack::roll),
+    ("2dup", Stack::two_dup),
+    ("2drop", Stack::two_drop),
+    ("depth", Stack::depth),
+    ("clear", Stack::clear),
+    (":=", Stack::assignment),
+    ("parseInteger", Stack::parse_integer),
+    ("parseFloat", Stack::parse_float),
+    ("words", Stack::words),
+    ("concat", strings::concat),
+    ("split", strings::split),
+    ("join", strings::join),
+    ("substr", strings::substr),
+    ("indexOf", strings::index_of),
+    ("contains", strings::contains),
+    ("startsWith", strings::starts_with),
+    ("endsWith", strings::ends_with),
+    ("toUpper", strings::to_upper),
+    ("toLower", strings::to_lower),
+    ("trim", strings::trim),
+    ("replace", strings::replace),
+    ("chars", strings::chars),
+    ("strlen", strings::strlen),
+    ("repeat", strings::repeat),
+    ("format", strings::format),
+    ("print", Stack::print),
+    ("read", Stack::read),
+    ("exec", Stack::exec),
+    ("dip", Stack::dip),
+    ("keep", Stack::keep),
+    ("bi", Stack::bi),
+    ("tri", Stack::tri),
+    ("cleave", Stack::cleave),
+    ("bi@", Stack::bi_at),
+    ("if", Stack::process_if),
+    ("map", Stack::process_map),
+    ("filter", Stack::process_filter),
+    ("each", Stack::process_each),
+    ("foldl", Stack::process_foldl),
+    ("foldr", Stack::process_foldr),
+    ("sortBy", Stack::process_sort_by),
+    ("any", Stack::process_any),
+    ("all", Stack::process_all),
+    ("find", Stack::process_find),
+    ("times", Stack::process_times),
+    ("loop", Stack::process_loop),
+    ("while", Stack::process_while),
+    ("+", add),
+    ("-", subtract),
+    ("*", multiply),
+    ("/", divide),
+    ("div", integer_divide),
+    ("<", less_than),
+    (">", greater_than),
+    ("&&", and),
+    ("||", or),
+    ("==", equal),
+    ("!=", not_equal),
+    ("<=", math::less_or_equal),
+    (">=", math::greater_or_equal),
+    ("mod", math::modulo),
+    ("rem", math::remainder),
+    ("abs", math::abs),
+    ("min", math::min),
+    ("max", math::max),
+    ("pow", math::pow),
+    ("sqrt", math::sqrt),
+    ("exp", math::exp),
+    ("log", math::log),
+    ("sin", math::sin),
+    ("cos", math::cos),
+    ("tan", math::tan),
+    ("gcd", math::gcd_word),
+    ("lcm", math::lcm),
+    ("pi", math::pi),
+    ("e", math::e),
+    ("band", bitwise::band),
+    ("bor", bitwise::bor),
+    ("bxor", bitwise::bxor),
+    ("bnot", bitwise::bnot),
+    ("shl", bitwise::shl),
+    ("shr", bitwise::shr),
+    ("popcount", bitwise::popcount),
+    ("not", not),
+    ("toFloat", to_float),
+    ("toInt", to_int),
+    ("toRational", to_rational),
+    ("numerator", numerator),
+    ("denominator", denominator),
+    ("round", round),
+    ("floor", floor),
+    ("ceil", ceil),
+    ("truncate", truncate),
+    ("head", Stack::head),
+    ("tail", Stack::tail),
+    ("empty", empty),
+    ("length", length),
+    ("take", Stack::take),
+    ("cons", Stack::cons),
+    ("append", Stack::append),
+    ("drop", Stack::drop),
+    ("nth", nth),
+    ("slice", slice),
+    ("reverse", reverse),
+    ("sort", sort),
+    ("zip", zip),
+    ("unzip", unzip),
+    ("flatten", flatten),
+    ("range", range),
+    ("sum", sum),
+    ("product", product),
+    ("uniq", uniq),
+    ("get", maps::get),
+    ("put", maps::put),
+    ("remove", maps::remove),
+    ("has", maps::has),
+    ("keys", maps::keys),
+    ("values", maps::values),
+    ("entries", maps::entries),
+    ("size", maps::size),
+];
+
+//finds the index of a built-in word in the table
+pub fn builtin_index(name: &str) -> Option<usize> {
+    BUILTINS.iter().position(|(builtin, _)| *builtin == name)
+}
+
+fn pop(stack: &mut Stack) -> Result<(), Error> {
+    stack.pop();
+    Ok(())
+}
+
+//arithemtic operations ensures that the types are compatible, ints are promoted to floats when they are used together
+fn add(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| add_values(&left, &right))
+}
+
+fn subtract(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(x, y)) => x.checked_sub(y).map(StackValue::Int).ok_or(Error::overflow("-")),
+        Some(Numbers::Rationals(x, y)) => x.checked_sub(y).map(StackValue::Rational),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Float(x - y)),
+        None => Err(Error::type_mismatch("-", "two numbers", &[&left, &right])),
+    })
+}
+
+fn multiply(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| multiply_values(&left, &right))
+}
+
+fn divide(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| {
+        let (x, y) = match promote(&left, &right) {
+            Some(Numbers::Ints(x, y)) => (x as f64, y as f64),
+            //dividing rationals stays exact
+            Some(Numbers::Rationals(x, y)) => return x.checked_div(y).map(StackValue::Rational),
+            Some(Numbers::Floats(x, y)) => (x, y),
+            None => return Err(Error::type_mismatch("/", "two numbers", &[&left, &right])),
+        };
+        //doesn't allow division by zero because it isn't possible
+        if y == 0.0 {
+            Err(Error::new(ErrorKind::DivisionByZero))
+        } else {
+            Ok(StackValue::Float(x / y))
+        }
+    })
+}
+
+fn integer_divide(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Int(x), StackValue::Int(y)) => {
+            if y == 0 {
+                Err(Error::new(ErrorKind::DivisionByZero))
+            } else {
+                //the smallest integer divided by -1 doesn't fit
+                x.checked_div(y).map(StackValue::Int).ok_or(Error::overflow("div"))
+            }
+        }
+        (left, right) => Err(Error::type_mismatch("div", "two integers", &[&left, &right])),
+    })
+}
+
+fn less_than(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(x, y)) => Ok(StackValue::Bool(x < y)),
+        Some(Numbers::Rationals(x, y)) => Ok(StackValue::Bool(x < y)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Bool(x < y)),
+        None => Err(Error::type_mismatch("<", "two numbers", &[&left, &right])),
+    })
+}
+
+fn greater_than(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(x, y)) => Ok(StackValue::Bool(x > y)),
+        Some(Numbers::Rationals(x, y)) => Ok(StackValue::Bool(x > y)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Bool(x > y)),
+        None => Err(Error::type_mismatch(">", "two numbers", &[&left, &right])),
+    })
+}
+
+fn and(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Bool(x), StackValue::Bool(y)) => Ok(StackValue::Bool(x && y)),
+        (left, right) => Err(Error::type_mismatch("&&", "two bools", &[&left, &right])),
+    })
+}
+
+fn or(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Bool(x), StackValue::Bool(y)) => Ok(StackValue::Bool(x || y)),
+        (left, right) => Err(Error::type_mismatch("||", "two bools", &[&left, &right])),
+    })
+}
+
+//needs two values to compare them, numbers are compared by value so 1 and 1.0 are equal
+fn equal(stack: &mut Stack) -> Result<(), Error> {
+    let rhs = stack.pop().ok_or(Error::new(ErrorKind::StackUnderflow))?;
+    let lhs = stack.pop().ok_or(Error::new(ErrorKind::StackUnderflow))?;
+    stack.push(StackValue::Bool(values_equal(&lhs, &rhs)));
+    Ok(())
+}
+
+fn not_equal(stack: &mut Stack) -> Result<(), Error> {
+    equal(stack)?;
+    not(stack)
+}
+
+fn not(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        //only works for int float and bool
+        StackValue::Int(x) => x.checked_neg().map(StackValue::Int).ok_or(Error::overflow("not")),
+        StackValue::Float(x) => Ok(StackValue::Float(-x)),
+        StackValue::Rational(x) => x.checked_neg().map(StackValue::Rational),
+        StackValue::Bool(x) => Ok(StackValue::Bool(!x)),
+        value => Err(Error::type_mismatch("not", "a number or a bool", &[&value])),
+    })
+}
+
+fn to_float(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match to_f64(&value) {
+        Some(x) => Ok(StackValue::Float(x)),
+        None => Err(Error::type_mismatch("toFloat", "a number", &[&value])),
+    })
+}
+
+//ints and floats become exact rationals
+fn to_rational(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => Ok(StackValue::Rational(Rational::from(x))),
+        StackValue::Rational(x) => Ok(StackValue::Rational(x)),
+        StackValue::Float(x) => Rational::from_f64(x).map(StackValue::Rational),
+        value => Err(Error::type_mismatch("toRational", "a number", &[&value])),
+    })
+}
+
+//an int is its own numerator with 1 as the denominator
+fn numerator(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => Ok(StackValue::Int(x)),
+        StackValue::Rational(x) => Ok(StackValue::Int(x.numerator())),
+        value => Err(Error::type_mismatch("numerator", "an integer or a rational", &[&value])),
+    })
+}
+
+fn denominator(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(_) => Ok(StackValue::Int(1)),
+        StackValue::Rational(x) => Ok(StackValue::Int(x.denominator())),
+        value => Err(Error::type_mismatch("denominator", "an integer or a rational", &[&value])),
+    })
+}
+
+//the rounding words turn floats and rationals into ints, ints are already whole so they are left as they are
+fn rounding(stack: &mut Stack, op: &str, round: fn(f64) -> f64, round_rational: fn(&Rational) -> i64) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => Ok(StackValue::Int(x)),
+        StackValue::Rational(x) => Ok(StackValue::Int(round_rational(&x))),
+        StackValue::Float(x) => float_to_int(round(x)),
+        value => Err(Error::type_mismatch(op, "a number", &[&value])),
+    })
+}
+
+//toInt drops the fraction like truncate
+fn to_int(stack: &mut Stack) -> Result<(), Error> {
+    rounding(stack, "toInt", f64::trunc, Rational::trunc)
+}
+
+fn round(stack: &mut Stack) -> Result<(), Error> {
+    rounding(stack, "round", f64::round, Rational::round)
+}
+
+fn floor(stack: &mut Stack) -> Result<(), Error> {
+    rounding(stack, "floor", f64::floor, Rational::floor)
+}
+
+fn ceil(stack: &mut Stack) -> Result<(), Error> {
+    rounding(stack, "ceil", f64::ceil, Rational::ceil)
+}
+
+fn truncate(stack: &mut Stack) -> Result<(), Error> {
+    rounding(stack, "truncate", f64::trunc, Rational::trunc)
+}
+
+fn empty(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| value.is_empty())
+}
+
+fn length(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| value.length())
+}
+
+//( list n -- value )
+fn nth(stack: &mut Stack) -> Result<(), Error> {
+    let index = stack.pop_int("nth")?;
+    stack.unary_op(|list| list.nth(index))
+}
+
+//( list start end -- list )
+fn slice(stack: &mut Stack) -> Result<(), Error> {
+    let end = stack.pop_count("slice")?;
+    let start = stack.pop_count("slice")?;
+    stack.unary_op(|list| list.slice(start, end))
+}
+
+fn reverse(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.reverse())
+}
+
+fn sort(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.sort())
+}
+
+//( list1 list2 -- pairs )
+fn zip(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| left.zip(&right))
+}
+
+//( pairs -- list1 list2 )
+fn unzip(stack: &mut Stack) -> Result<(), Error> {
+    let (firsts, seconds) = stack.pop_value()?.unzip()?;
+    stack.push(firsts);
+    stack.push(seconds);
+    Ok(())
+}
+
+fn flatten(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.flatten())
+}
+
+//( start end step -- list ) the numbers from start to end, including end if the steps land on it
+fn range(stack: &mut Stack) -> Result<(), Error> {
+    let step = stack.pop_int("range")?;
+    let end = stack.pop_int("range")?;
+    let start = stack.pop_int("range")?;
+    if step == 0 {
+        return Err(Error::invalid("The step of a range can't be 0"));
+    }
+    let mut numbers = Vec::new();
+    let mut number = Some(start);
+    while let Some(n) = number.filter(|&n| if step > 0 { n <= end } else { n >= end }) {
+        numbers.push(StackValue::Int(n));
+        number = n.checked_add(step);
+    }
+    stack.push(StackValue::Vec(List::from(numbers)));
+    Ok(())
+}
+
+fn sum(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.sum())
+}
+
+fn product(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.product())
+}
+
+fn uniq(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|list| list.uniq())
+}
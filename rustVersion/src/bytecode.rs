@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use crate::builtins::*;
+use crate::error::*;
+use crate::list::List;
+use crate::lexer::Span;
+use crate::parser::*;
+use crate::stackvalues::*;
+
+//the instructions the stack runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instr {
+    //pushes the constant at the index
+    Push(usize),
+    //calls the built-in word at the index in BUILTINS
+    CallBuiltin(usize),
+    //calls the variable or user word named by the index into names, words bound to a quotation run it
+    CallWord(usize),
+    //continues at the given instruction
+    Jump(usize),
+    //pops a bool and continues at the given instruction when it is false
+    JumpIfFalse(usize),
+    //exec, runs the quotation on the stack the same way as a call to a user word
+    Exec,
+    //if with its blocks on the stack, runs the chosen one the same way as exec
+    If,
+    //ends the chunk
+    Return,
+}
+
+//compiled code of a program or a quotation
+#[derive(Clone)]
+pub struct Chunk {
+    pub code: Vec<Instr>,
+    pub constants: Vec<StackValue>,
+    pub names: Vec<String>,
+    //where each instruction came from in the source, the return at the end has none
+    pub spans: Vec<Span>,
+    //the nodes it was compiled from, used to show the quotation
+    pub source: Vec<Node>,
+}
+
+//two chunks are the same if they were compiled from the same code
+impl PartialEq for Chunk {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+//shows the source nodes so printing a quotation doesn't show the instructions
+impl Debug for Chunk {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.source)
+    }
+}
+
+//compiles the nodes of a program or quotation into a chunk ending with a return
+pub fn compile(nodes: &[Node]) -> Result<Chunk, Error> {
+    let mut chunk = Chunk {
+        code: Vec::new(),
+        constants: Vec::new(),
+        names: Vec::new(),
+        spans: Vec::new(),
+        source: nodes.to_vec(),
+    };
+    chunk.compile_nodes(nodes)?;
+    chunk.code.push(Instr::Return);
+    Ok(chunk)
+}
+
+//the value of a literal, list or quotation node, lists have to be of same type while tuples can mix them
+fn constant(node: &Node) -> Result<StackValue, Error> {
+    match &node.kind {
+        NodeKind::Literal(value) => Ok(value.clone()),
+        NodeKind::List(elements) => {
+            let elements = elements.iter().map(constant).collect::<Result<Vec<StackValue>, _>>()?;
+            if StackValue::all_same_type(&elements) {
+                Ok(StackValue::Vec(List::from(elements)))
+            } else {
+                Err(Error::parse("Lists with mixed types are not allowed, use a ( ) tuple to mix them").at(&node.span))
+            }
+        }
+        NodeKind::Tuple(elements) => Ok(StackValue::Vec(elements.iter().map(constant).collect::<Result<_, _>>()?)),
+        NodeKind::Map(elements) => {
+            if elements.len() % 2 != 0 {
+                return Err(Error::parse("A map literal needs a value after every key").at(&node.span));
+            }
+            let mut map = BTreeMap::new();
+            for pair in elements.chunks(2) {
+                let key = Key::from_value(&constant(&pair[0])?)
+                    .ok_or(Error::parse("Map keys have to be bools, integers, strings or symbols").at(&pair[0].span))?;
+                map.insert(key, constant(&pair[1])?);
+            }
+            Ok(StackValue::Map(map))
+        }
+        NodeKind::Quotation(body) => Ok(StackValue::Quotation(Rc::new(compile(body)?))),
+        _ => Err(Error::parse("Expected a value").at(&node.span)),
+    }
+}
+
+impl Chunk {
+    fn compile_nodes(&mut self, nodes: &[Node]) -> Result<(), Error> {
+        for node in nodes {
+            let span = &node.span;
+            match &node.kind {
+                NodeKind::Word(word) => self.compile_word(word, span),
+                NodeKind::Assign => self.compile_word(":=", span),
+                //a definition is the same as assigning the quotation to the name
+                NodeKind::Fun(name, body) => {
+                    self.push_constant(StackValue::Symbol(name.clone()), span);
+                    self.push_constant(StackValue::Quotation(Rc::new(compile(body)?)), span);
+                    self.compile_word(":=", span);
+                }
+                //the false block is jumped to when the condition is false, the true block jumps past it
+                NodeKind::If(true_block, false_block) => {
+                    let jump_if_false = self.code.len();
+                    self.emit(Instr::JumpIfFalse(0), span);
+                    self.compile_nodes(true_block)?;
+                    let jump = self.code.len();
+                    self.emit(Instr::Jump(0), span);
+                    self.code[jump_if_false] = Instr::JumpIfFalse(self.code.len());
+                    self.compile_nodes(false_block)?;
+                    self.code[jump] = Instr::Jump(self.code.len());
+                }
+                //the quotations after the word are pushed before the word is called
+                NodeKind::Apply(word, blocks) => {
+                    for block in blocks {
+                        self.push_constant(StackValue::Quotation(Rc::new(compile(block)?)), span);
+                    }
+                    self.compile_word(word, span);
+                }
+                _ => self.push_constant(constant(node)?, span),
+            }
+        }
+        Ok(())
+    }
+
+    fn emit(&mut self, instr: Instr, span: &Span) {
+        self.code.push(instr);
+        self.spans.push(span.clone());
+    }
+
+    fn push_constant(&mut self, value: StackValue, span: &Span) {
+        self.constants.push(value);
+        self.emit(Instr::Push(self.constants.len() - 1), span);
+    }
+
+    //built-ins are called by index, everything else is looked up by name when it runs
+    fn compile_word(&mut self, word: &str, span: &Span) {
+        //these run their quotation inside the virtual machine so recursing through them doesn't use the rust stack
+        match word {
+            "exec" => return self.emit(Instr::Exec, span),
+            "if" => return self.emit(Instr::If, span),
+            _ => (),
+        }
+        match builtin_index(word) {
+            Some(index) => self.emit(Instr::CallBuiltin(index), span),
+            None => {
+                let index = match self.names.iter().position(|name| name == word) {
+                    Some(index) => index,
+                    None => {
+                        self.names.push(word.to_string());
+                        self.names.len() - 1
+                    }
+                };
+                self.emit(Instr::CallWord(index), span);
+            }
+        }
+    }
+
+    //the name of the word the instruction at pc calls, used in the call trace of errors
+    pub fn called_word(&self, pc: usize) -> &str {
+        match self.code[pc] {
+            Instr::CallBuiltin(index) => BUILTINS[index].0,
+            Instr::CallWord(index) => &self.names[index],
+            Instr::JumpIfFalse(_) | Instr::If => "if",
+            Instr::Exec => "exec",
+            _ => "",
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::lexer::Span;
+use crate::stackvalues::*;
+
+//the different things that can go wrong
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    //a word needed more values than there were on the stack
+    StackUnderflow,
+    //a word got values of the wrong type, found shows the values it got
+    TypeMismatch {
+        op: String,
+        expected: &'static str,
+        found: String,
+    },
+    DivisionByZero,
+    //the result of the word on integers doesn't fit in 64 bits
+    Overflow(String),
+    //a word that is neither a built-in nor a symbol
+    UnknownWord(String),
+    //the source couldn't be turned into a program
+    ParseError(String),
+    //a value that has the right type but can't be used, like the head of an empty list
+    InvalidValue(String),
+    RedefineBuiltin(String),
+    CallStackOverflow,
+    IoError(String),
+}
+
+//an error together with where in the source it happened and the words that were called to get there
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    //boxed to keep results with errors small
+    pub span: Option<Box<Span>>,
+    //the words that were being run when the error happened and where they were called, innermost first
+    pub trace: Vec<(String, Span)>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            span: None,
+            trace: Vec::new(),
+        }
+    }
+
+    //the word got the values in found when it expected something else
+    pub fn type_mismatch(op: &str, expected: &'static str, found: &[&StackValue]) -> Self {
+        let found = found.iter().map(|value| format!("{:?}", value)).collect::<Vec<String>>().join(" and ");
+        Error::new(ErrorKind::TypeMismatch {
+            op: op.to_string(),
+            expected,
+            found,
+        })
+    }
+
+    pub fn parse(message: &str) -> Self {
+        Error::new(ErrorKind::ParseError(message.to_string()))
+    }
+
+    pub fn overflow(op: &str) -> Self {
+        Error::new(ErrorKind::Overflow(op.to_string()))
+    }
+
+    pub fn invalid(message: &str) -> Self {
+        Error::new(ErrorKind::InvalidValue(message.to_string()))
+    }
+
+    //sets where the error happened, unless it is already known
+    pub fn at(mut self, span: &Span) -> Self {
+        if self.span.is_none() {
+            self.span = Some(Box::new(span.clone()));
+        }
+        self
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::StackUnderflow => write!(f, "Not enough elements in the stack"),
+            ErrorKind::TypeMismatch { op, expected, found } => {
+                write!(f, "Mismatched types for '{}': expected {}, found {}", op, expected, found)
+            }
+            ErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            ErrorKind::Overflow(op) => write!(f, "Integer overflow in '{}'", op),
+            ErrorKind::UnknownWord(word) => write!(f, "Unknown word '{}'", word),
+            ErrorKind::ParseError(message) => write!(f, "{}", message),
+            ErrorKind::InvalidValue(message) => write!(f, "{}", message),
+            ErrorKind::RedefineBuiltin(word) => write!(f, "The built-in word '{}' can't be redefined", word),
+            ErrorKind::CallStackOverflow => write!(f, "Call stack overflow"),
+            ErrorKind::IoError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+//shows the message followed by the source line with the offending part underlined, like
+//Division by zero
+//  --> <stdin>:1:5
+//   |
+// 1 | 1 0 div
+//   |     ^^^
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(span) = &self.span {
+            let line = span.source.lines().nth(span.line - 1).unwrap_or("");
+            let number = span.line.to_string();
+            let pad = " ".repeat(number.len());
+            write!(f, "\n{} --> {}:{}:{}", pad, span.file, span.line, span.column)?;
+            write!(f, "\n{} |", pad)?;
+            write!(f, "\n{} | {}", number, line)?;
+            write!(f, "\n{} | {}{}", pad, " ".repeat(span.column - 1), "^".repeat(span.len.max(1)))?;
+        }
+        for (word, span) in &self.trace {
+            write!(f, "\n  in '{}' called at {}:{}:{}", word, span.file, span.line, span.column)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::error::*;
+use crate::io::*;
+use crate::stack::*;
+use crate::stackvalues::*;
+
+//the interpreter other programs use to run bprog code, e.g.
+//let mut interpreter = Interpreter::new();
+//interpreter.push(StackValue::Int(2));
+//interpreter.eval("3 +")?;
+//assert_eq!(interpreter.pop(), Some(StackValue::Int(5)));
+pub struct Interpreter {
+    stack: Stack,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter { stack: Stack::new() }
+    }
+
+    //an interpreter that prints and reads through the given io, e.g. a ScriptedIo in tests
+    pub fn with_io<I: Io + 'static>(io: I) -> Self {
+        Interpreter { stack: Stack::with_io(io) }
+    }
+
+    //the io print and read use, so a front end can write its own output next to it
+    pub fn io(&mut self) -> &mut dyn Io {
+        self.stack.io.as_mut()
+    }
+
+    //runs the code, when it fails the stack is left as it was before
+    pub fn eval(&mut self, source: &str) -> Result<(), Error> {
+        self.stack.parse_source("<eval>", source)
+    }
+
+    //same as eval but errors point into the named source, like a file name
+    pub fn eval_source(&mut self, name: &str, source: &str) -> Result<(), Error> {
+        self.stack.parse_source(name, source)
+    }
+
+    pub fn push(&mut self, value: StackValue) {
+        self.stack.push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<StackValue> {
+        self.stack.pop()
+    }
+
+    //the values on the stack, the top is the last one
+    pub fn stack(&self) -> &[StackValue] {
+        &self.stack.data
+    }
+
+    //makes a rust function callable as a word, arity is how many values it takes from the stack, e.g.
+    //interpreter.register_word("copy", 1, |stack| {
+    //    let value = stack.pop().unwrap();
+    //    stack.push(value.clone());
+    //    stack.push(value);
+    //    Ok(())
+    //})?;
+    pub fn register_word<F>(&mut self, name: &str, arity: usize, word: F) -> Result<(), Error>
+    where
+        F: Fn(&mut Stack) -> Result<(), Error> + 'static,
+    {
+        self.stack.register_word(name, arity, word)
+    }
+
+    //the value a variable or user-defined word is bound to
+    pub fn get_symbol(&self, name: &str) -> Option<&StackValue> {
+        self.stack.symbols.get(name)
+    }
+
+    //binds a value to the name the same way := does, built-in words can't be redefined
+    pub fn set_symbol(&mut self, name: &str, value: StackValue) -> Result<(), Error> {
+        if self.stack.is_reserved(name) {
+            return Err(Error::new(ErrorKind::RedefineBuiltin(name.to_string())));
+        }
+        self.stack.symbols.insert(name.to_string(), value);
+        Ok(())
+    }
+}
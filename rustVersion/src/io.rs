@@ -0,0 +1,116 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Write};
+use std::rc::Rc;
+
+//where print writes to and read reads from, so the interpreter can be used without a terminal
+pub trait Io {
+    fn write_line(&mut self, line: &str) -> io::Result<()>;
+    //the next line without the line break, None when there is no more input
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+    fn write_err(&mut self, line: &str) -> io::Result<()>;
+}
+
+//the terminal, used by default
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(io::stdout(), "{}", line)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+    }
+
+    fn write_err(&mut self, line: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "{}", line)
+    }
+}
+
+//keeps everything written in memory and has no input, clones share the same lines
+//so one can be given to the interpreter and the other used to look at the output
+#[derive(Clone, Default)]
+pub struct BufferIo {
+    output: Rc<RefCell<Vec<String>>>,
+    errors: Rc<RefCell<Vec<String>>>,
+}
+
+impl BufferIo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //the lines written so far
+    pub fn output(&self) -> Vec<String> {
+        self.output.borrow().clone()
+    }
+
+    //the error lines written so far
+    pub fn errors(&self) -> Vec<String> {
+        self.errors.borrow().clone()
+    }
+}
+
+impl Io for BufferIo {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.output.borrow_mut().push(line.to_string());
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(None)
+    }
+
+    fn write_err(&mut self, line: &str) -> io::Result<()> {
+        self.errors.borrow_mut().push(line.to_string());
+        Ok(())
+    }
+}
+
+//reads from the lines it was given and keeps what is written in memory like BufferIo
+#[derive(Clone)]
+pub struct ScriptedIo {
+    input: Rc<RefCell<VecDeque<String>>>,
+    buffer: BufferIo,
+}
+
+impl ScriptedIo {
+    pub fn new(input: &[&str]) -> Self {
+        ScriptedIo {
+            input: Rc::new(RefCell::new(input.iter().map(|line| line.to_string()).collect())),
+            buffer: BufferIo::new(),
+        }
+    }
+
+    pub fn output(&self) -> Vec<String> {
+        self.buffer.output()
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        self.buffer.errors()
+    }
+
+    //the input lines that haven't been read yet
+    pub fn remaining(&self) -> usize {
+        self.input.borrow().len()
+    }
+}
+
+impl Io for ScriptedIo {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.buffer.write_line(line)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(self.input.borrow_mut().pop_front())
+    }
+
+    fn write_err(&mut self, line: &str) -> io::Result<()> {
+        self.buffer.write_err(line)
+    }
+}
@@ -0,0 +1,373 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::rc::Rc;
+
+use crate::error::*;
+use crate::numeric::Rational;
+
+//where in the source a token came from, so errors can point at the offending code
+#[derive(Clone, PartialEq)]
+pub struct Span {
+    pub file: Rc<str>,
+    //the whole source the span is in, so the line can be shown even after the source is gone
+    pub source: Rc<str>,
+    pub line: usize,
+    pub column: usize,
+    pub len: usize,
+}
+
+//the different kinds of tokens the lexer recognizes
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Int(i64),
+    Float(f64),
+    Rational(Rational),
+    String(String),
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    //{| and |} around a map literal
+    LMap,
+    RMap,
+    //( ) around a tuple, a list that can mix types
+    LParen,
+    RParen,
+    //the .. used in list ranges
+    DotDot,
+    Word(String),
+    //quoted symbols, written either as :name or 'name'
+    Symbol(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+//only the position is shown, the source would clutter the output
+impl Debug for Span {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+//shows the token the way it was written in the source
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Int(value) => write!(f, "{}", value),
+            TokenKind::Float(value) => write!(f, "{:?}", value),
+            TokenKind::Rational(value) => write!(f, "{}r", value),
+            TokenKind::String(value) => write!(f, "{:?}", value),
+            TokenKind::LBracket => write!(f, "["),
+            TokenKind::RBracket => write!(f, "]"),
+            TokenKind::LBrace => write!(f, "{{"),
+            TokenKind::RBrace => write!(f, "}}"),
+            TokenKind::LMap => write!(f, "{{|"),
+            TokenKind::RMap => write!(f, "|}}"),
+            TokenKind::LParen => write!(f, "("),
+            TokenKind::RParen => write!(f, ")"),
+            TokenKind::DotDot => write!(f, ".."),
+            TokenKind::Word(word) => write!(f, "{}", word),
+            TokenKind::Symbol(symbol) => write!(f, ":{}", symbol),
+        }
+    }
+}
+
+//characters that end a bare word or number
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}' | '(' | ')' | '"')
+}
+
+//reads an integer written in decimal or with a 0x, 0b or 0o prefix, underscores can be used to separate the digits
+pub fn parse_int(text: &str) -> Option<i64> {
+    let text = text.replace('_', "");
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(&text)),
+    };
+    let radix = match unsigned.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0b" | "0B") => 2,
+        Some("0o" | "0O") => 8,
+        _ => return text.parse().ok(),
+    };
+    let digits = &unsigned[2..];
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    //the prefixed literals are the bits of the integer, so 0xFFFFFFFFFFFFFFFF is -1 and
+    //-0x8000000000000000 is the same smallest integer as 0x8000000000000000
+    let value = u64::from_str_radix(digits, radix).ok()? as i64;
+    Some(if negative { value.wrapping_neg() } else { value })
+}
+
+//whether the text is written like an integer, in decimal or with a prefix, whether it fits or not
+fn is_int_literal(text: &str) -> bool {
+    let text = text.replace('_', "");
+    let unsigned = text.strip_prefix(['-', '+']).unwrap_or(&text);
+    let (radix, digits) = match unsigned.get(..2) {
+        Some("0x" | "0X") => (16, &unsigned[2..]),
+        Some("0b" | "0B") => (2, &unsigned[2..]),
+        Some("0o" | "0O") => (8, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix))
+}
+
+//decides what a bare run of characters is, a number, a quoted symbol or a word
+fn classify(text: &str) -> Result<TokenKind, Error> {
+    let digits = text.trim_start_matches(['-', '+']);
+    let numeric = digits.starts_with(|c: char| c.is_ascii_digit())
+        || (digits.starts_with('.') && digits[1..].starts_with(|c: char| c.is_ascii_digit()));
+
+    if numeric {
+        if let Some(value) = parse_int(text) {
+            return Ok(TokenKind::Int(value));
+        }
+        //only a literal with a fraction or an exponent is a float, an integer too big for 64 bits is an error
+        //instead of a float that lost its last digits
+        if text.contains(['.', 'e', 'E']) {
+            if let Ok(value) = text.replace('_', "").parse::<f64>() {
+                return Ok(TokenKind::Float(value));
+            }
+        }
+        if is_int_literal(text) {
+            return Err(Error::parse("The integer literal doesn't fit in 64 bits"));
+        }
+        //rationals are written as numerator/denominator followed by r, e.g. 1/3r
+        if let Some((numerator, denominator)) = text.strip_suffix('r').and_then(|fraction| fraction.split_once('/')) {
+            if let (Some(numerator), Some(denominator)) = (parse_int(numerator), parse_int(denominator)) {
+                return Rational::new(numerator, denominator)
+                    .map(TokenKind::Rational)
+                    .map_err(|e| match e.kind {
+                        ErrorKind::DivisionByZero => Error::parse("A rational can't have a zero denominator"),
+                        _ => Error::parse("The rational literal is too large"),
+                    });
+            }
+        }
+    }
+    if text.len() > 2 && text.starts_with('\'') && text.ends_with('\'') {
+        return Ok(TokenKind::Symbol(text[1..text.len() - 1].to_string()));
+    }
+    //:= is the assignment word and not a symbol named =
+    if text.len() > 1 && text.starts_with(':') && text != ":=" {
+        return Ok(TokenKind::Symbol(text[1..].to_string()));
+    }
+    Ok(TokenKind::Word(text.to_string()))
+}
+
+const UNTERMINATED: &str = "Unterminated string literal";
+
+//the number of # in the r"text" or r#"text"# starting the chars after the r, None if they don't start a raw string
+fn raw_string_hashes(chars: &[char]) -> Option<usize> {
+    let hashes = chars.iter().take_while(|c| **c == '#').count();
+    (chars.get(hashes) == Some(&'"')).then_some(hashes)
+}
+
+//reads a string literal after its opening quotes up to and past the closing ones, moving the position along,
+//errors are returned with the line and column they happened at
+fn read_string(
+    chars: &[char],
+    index: &mut usize,
+    line: &mut usize,
+    column: &mut usize,
+    closing: &[char],
+    escapes: bool,
+) -> Result<String, (String, usize, usize)> {
+    let mut value = String::new();
+    loop {
+        if chars[*index..].starts_with(closing) {
+            *index += closing.len();
+            *column += closing.len();
+            return Ok(value);
+        }
+        match chars.get(*index) {
+            Some('\n') => {
+                value.push('\n');
+                *line += 1;
+                *column = 1;
+                *index += 1;
+            }
+            Some('\\') if escapes => {
+                let (escaped, len) = escape(&chars[*index + 1..]).map_err(|message| (message, *line, *column))?;
+                value.push(escaped);
+                *index += len + 1;
+                *column += len + 1;
+            }
+            Some(&other) => {
+                value.push(other);
+                *index += 1;
+                *column += 1;
+            }
+            None => return Err((UNTERMINATED.to_string(), *line, *column)),
+        }
+    }
+}
+
+//the character an escape sequence after a backslash stands for, and how many characters the sequence is
+fn escape(chars: &[char]) -> Result<(char, usize), String> {
+    let escaped = match chars.first() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('0') => '\0',
+        Some('\\') => '\\',
+        Some('"') => '"',
+        Some('\'') => '\'',
+        //unicode escapes are written as \u{1F600}
+        Some('u') => {
+            let end = chars.iter().position(|c| *c == '}');
+            let code: Option<String> = match (chars.get(1), end) {
+                (Some('{'), Some(end)) => Some(chars[2..end].iter().collect()),
+                _ => None,
+            };
+            return code
+                .and_then(|code| u32::from_str_radix(&code, 16).ok())
+                .and_then(char::from_u32)
+                .map(|c| (c, end.unwrap() + 1))
+                .ok_or("Invalid unicode escape, it is written as \\u{hex code}".to_string());
+        }
+        Some(other) => return Err(format!("Unknown escape sequence '\\{}'", other)),
+        None => return Err(UNTERMINATED.to_string()),
+    };
+    Ok((escaped, 1))
+}
+
+//splits the source into typed tokens, each with the span it was found at
+pub fn tokenize(file: &str, input: &str) -> Result<Vec<Token>, Error> {
+    let file: Rc<str> = Rc::from(file);
+    let source: Rc<str> = Rc::from(input);
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    //open brackets and braces, so we can report the ones that are never closed
+    let mut open: Vec<Token> = Vec::new();
+    let mut index = 0;
+    let mut line = 1;
+    let mut column = 1;
+
+    let span = |line: usize, column: usize, len: usize| Span {
+        file: file.clone(),
+        source: source.clone(),
+        line,
+        column,
+        len,
+    };
+
+    while index < chars.len() {
+        let c = chars[index];
+        let start_column = column;
+
+        //commas are allowed as separators in lists and quotations so they work like whitespace
+        if c == '\n' {
+            line += 1;
+            column = 1;
+            index += 1;
+            continue;
+        }
+        if c.is_whitespace() || c == ',' {
+            column += 1;
+            index += 1;
+            continue;
+        }
+
+        let map_bracket = |opening: char| chars.get(index + 1) == Some(&if opening == '{' { '|' } else { '}' });
+        match c {
+            '[' | '{' | '(' => {
+                let (kind, len) = match c {
+                    '[' => (TokenKind::LBracket, 1),
+                    '(' => (TokenKind::LParen, 1),
+                    _ if map_bracket('{') => (TokenKind::LMap, 2),
+                    _ => (TokenKind::LBrace, 1),
+                };
+                let token = Token { kind, span: span(line, start_column, len) };
+                open.push(token.clone());
+                tokens.push(token);
+                index += len;
+                column += len;
+            }
+            _ if c == ']' || c == '}' || c == ')' || (c == '|' && map_bracket('|')) => {
+                let (kind, opening, len) = match c {
+                    ']' => (TokenKind::RBracket, TokenKind::LBracket, 1),
+                    ')' => (TokenKind::RParen, TokenKind::LParen, 1),
+                    '}' => (TokenKind::RBrace, TokenKind::LBrace, 1),
+                    _ => (TokenKind::RMap, TokenKind::LMap, 2),
+                };
+                let here = span(line, start_column, len);
+                //the closing one has to match the innermost open one
+                match open.pop() {
+                    Some(token) if token.kind == opening => (),
+                    Some(_) => {
+                        return Err(Error::parse("Mismatched closing bracket").at(&here));
+                    }
+                    None => {
+                        return Err(Error::parse("Unmatched closing bracket").at(&here));
+                    }
+                }
+                tokens.push(Token { kind, span: here });
+                index += len;
+                column += len;
+            }
+            //"text" with escapes, """text""" that can span lines more easily and r"text" or r#"text"# without escapes
+            _ if c == '"' || (c == 'r' && raw_string_hashes(&chars[index + 1..]).is_some()) => {
+                let start_line = line;
+                let (opening, closing, escapes) = if c == 'r' {
+                    let hashes = raw_string_hashes(&chars[index + 1..]).unwrap();
+                    let closing: Vec<char> = std::iter::once('"').chain(std::iter::repeat_n('#', hashes)).collect();
+                    (hashes + 2, closing, false)
+                } else if chars[index..].starts_with(&['"', '"', '"']) {
+                    (3, vec!['"', '"', '"'], true)
+                } else {
+                    (1, vec!['"'], true)
+                };
+                index += opening;
+                column += opening;
+                //a triple quoted string starting on its own line doesn't include that line break
+                if opening == 3 && chars.get(index) == Some(&'\n') {
+                    index += 1;
+                    line += 1;
+                    column = 1;
+                }
+                let value = read_string(&chars, &mut index, &mut line, &mut column, &closing, escapes).map_err(|(message, error_line, error_column)| {
+                    let (error_line, error_column) = if message == UNTERMINATED { (start_line, start_column) } else { (error_line, error_column) };
+                    Error::parse(&message).at(&span(error_line, error_column, 1))
+                })?;
+                let len = if line == start_line { column - start_column } else { 1 };
+                tokens.push(Token {
+                    kind: TokenKind::String(value),
+                    span: span(start_line, start_column, len),
+                });
+            }
+            _ if c == '.' && chars.get(index + 1) == Some(&'.') => {
+                tokens.push(Token { kind: TokenKind::DotDot, span: span(line, start_column, 2) });
+                index += 2;
+                column += 2;
+            }
+            _ => {
+                //a bare run ends at a delimiter, at the .. of a range or at the |} closing a map
+                let start = index;
+                let ends_run = |index: usize| {
+                    let pair = (chars[index], chars.get(index + 1).copied());
+                    is_delimiter(chars[index]) || pair == ('.', Some('.')) || pair == ('|', Some('}'))
+                };
+                while index < chars.len() && !ends_run(index) {
+                    index += 1;
+                }
+                let text: String = chars[start..index].iter().collect();
+                column += index - start;
+                let here = span(line, start_column, index - start);
+                tokens.push(Token {
+                    kind: classify(&text).map_err(|e| e.at(&here))?,
+                    span: here,
+                });
+            }
+        }
+    }
+
+    if let Some(unclosed) = open.pop() {
+        return Err(Error::parse("Missing closing bracket").at(&unclosed.span));
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,25 @@
+//bprog as a library, so it can be embedded in other rust programs through the Interpreter
+pub mod bitwise;
+pub mod builtins;
+pub mod bytecode;
+pub mod error;
+pub mod interpreter;
+pub mod io;
+pub mod lexer;
+pub mod list;
+pub mod maps;
+pub mod math;
+pub mod numeric;
+pub mod parser;
+pub mod stack;
+pub mod stackvalues;
+pub mod strings;
+
+pub use crate::error::{Error, ErrorKind};
+pub use crate::interpreter::Interpreter;
+pub use crate::io::{BufferIo, Io, ScriptedIo, StdIo};
+pub use crate::list::List;
+pub use crate::stackvalues::StackValue;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,200 @@
+use std::fmt::{self, Debug, Formatter};
+use std::rc::Rc;
+
+use crate::numeric::values_equal;
+use crate::stackvalues::StackValue;
+
+//a list made of shared cells, each holding a value and the rest of the list after it. Copying a list,
+//taking its tail and consing onto it only make or share cells, so they don't copy the values however
+//many other lists share them
+#[derive(Clone, Default)]
+pub struct List {
+    head: Option<Rc<Cell>>,
+    len: usize,
+}
+
+struct Cell {
+    value: StackValue,
+    next: Option<Rc<Cell>>,
+}
+
+impl List {
+    pub fn new() -> Self {
+        List::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    //the values from the head to the end of the list
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { cell: self.head.as_deref(), remaining: self.len }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&StackValue> {
+        self.iter().nth(index)
+    }
+
+    pub fn first(&self) -> Option<&StackValue> {
+        self.head.as_ref().map(|cell| &cell.value)
+    }
+
+    //compares the way == does, so a list with 1 in it contains 1.0
+    pub fn contains(&self, value: &StackValue) -> bool {
+        self.iter().any(|element| values_equal(element, value))
+    }
+
+    //everything but the head, the cells stay shared
+    pub fn tail(&self) -> Option<List> {
+        let head = self.head.as_ref()?;
+        Some(List { head: head.next.clone(), len: self.len - 1 })
+    }
+
+    //the list without its first count values, sharing the cells after them
+    pub fn skip(&self, count: usize) -> List {
+        let mut list = self.clone();
+        for _ in 0..count.min(self.len) {
+            list = list.tail().unwrap();
+        }
+        list
+    }
+
+    //the list with the value put in front
+    pub fn cons(self, value: StackValue) -> List {
+        let len = self.len + 1;
+        List { head: Some(Rc::new(Cell { value, next: self.head.clone() })), len }
+    }
+
+    //this list followed by the other one, the cells of this list are copied and the other one is shared
+    pub fn append(self, other: List) -> List {
+        let values = self.to_vec();
+        values.into_iter().rev().fold(other, |list, value| list.cons(value))
+    }
+
+    pub fn to_vec(&self) -> Vec<StackValue> {
+        self.iter().cloned().collect()
+    }
+
+    //true when both lists start at the same cell, so they share all of their values
+    pub fn ptr_eq(&self, other: &List) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+//the cells are freed one after the other, letting them free each other would use the rust stack for every cell
+impl Drop for List {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(cell) = next {
+            match Rc::try_unwrap(cell) {
+                Ok(mut cell) => next = cell.next.take(),
+                //another list still uses the rest
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl From<Vec<StackValue>> for List {
+    fn from(values: Vec<StackValue>) -> Self {
+        values.into_iter().rev().fold(List::new(), |list, value| list.cons(value))
+    }
+}
+
+impl FromIterator<StackValue> for List {
+    fn from_iter<I: IntoIterator<Item = StackValue>>(iter: I) -> Self {
+        List::from(iter.into_iter().collect::<Vec<StackValue>>())
+    }
+}
+
+pub struct Iter<'a> {
+    cell: Option<&'a Cell>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a StackValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.cell?;
+        self.cell = cell.next.as_deref();
+        self.remaining -= 1;
+        Some(&cell.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+//gives the values from the head, they are only cloned if another list shares their cells
+pub struct IntoIter {
+    list: List,
+}
+
+impl Iterator for IntoIter {
+    type Item = StackValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = self.list.head.take()?;
+        self.list.len -= 1;
+        match Rc::try_unwrap(cell) {
+            Ok(mut cell) => {
+                self.list.head = cell.next.take();
+                Some(cell.value)
+            }
+            Err(cell) => {
+                self.list.head = cell.next.clone();
+                Some(cell.value.clone())
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for List {
+    type Item = StackValue;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { list: self }
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a StackValue;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+//shown like a vec so lists print the same as before
+impl Debug for List {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -2,16 +2,31 @@ use std::io::{self, Write};
 use std::env;
 use std::fs;
 
-//imports stack.rs to use it's public functionalities
-mod stackvalues;
-mod stack;
-use crate::stack::*;
+//the interpreter itself lives in the library, this is only the terminal and file front end
+use bprog::{Error, ErrorKind, Interpreter};
 
 fn print_commands(){
     println!("?s to see stack\n?q to quit");
     println!("dup ( x -- x x )");
     println!("swap ( x y -- y x )");
     println!("pop ( x -- ) ");
+    println!("over ( x y -- x y x )");
+    println!("rot ( x y z -- y z x )");
+    println!("-rot ( x y z -- z x y )");
+    println!("nip ( x y -- y )");
+    println!("tuck ( x y -- y x y )");
+    println!("pick ( ... n -- ... x )");
+    println!("roll ( ... n -- ... )");
+    println!("2dup ( x y -- x y x y )");
+    println!("2drop ( x y -- )");
+    println!("depth ( -- n )");
+    println!("clear ( ... -- )");
+    println!("dip ( x quot -- x )");
+    println!("keep ( x quot -- ... x )");
+    println!("bi ( x p q -- ... )");
+    println!("tri ( x p q r -- ... )");
+    println!("cleave ( x quots -- ... )");
+    println!("bi@ ( x y quot -- ... )");
     println!("push ( x ++ )");
     println!("print: (x--)");
     println!("read: (--x)");
@@ -23,6 +38,32 @@ fn print_commands(){
     println!("< ( x y -- bool)");
     println!("> ( x y -- bool)");
     println!("== ( x y -- bool )");
+    println!("!= ( x y -- bool )");
+    println!("<= ( x y -- bool )");
+    println!(">= ( x y -- bool )");
+    println!("mod ( x y -- remainder )");
+    println!("rem ( x y -- remainder )");
+    println!("abs ( x -- abs )");
+    println!("min ( x y -- min )");
+    println!("max ( x y -- max )");
+    println!("pow ( x y -- x_to_the_y )");
+    println!("sqrt exp log sin cos tan ( x -- float )");
+    println!("gcd ( x y -- gcd )");
+    println!("lcm ( x y -- lcm )");
+    println!("pi e ( -- float )");
+    println!("band bor bxor ( x y -- int )");
+    println!("bnot ( x -- int )");
+    println!("shl shr ( x n -- int )");
+    println!("popcount ( x -- int )");
+    println!("toFloat ( x -- float )");
+    println!("toInt ( x -- int )");
+    println!("toRational ( x -- rational )");
+    println!("numerator ( x -- int )");
+    println!("denominator ( x -- int )");
+    println!("round ( x -- int )");
+    println!("floor ( x -- int )");
+    println!("ceil ( x -- int )");
+    println!("truncate ( x -- int )");
     println!("True - literal");
     println!("False - literal");
     println!("&& ( x y -- bool )");
@@ -32,15 +73,53 @@ fn print_commands(){
     println!("tail ( list -- tail )");
     println!("empty ( list -- bool )");
     println!("length ( list -- len )");
+    println!("concat ( s1 s2 -- s )");
+    println!("split ( s separator -- list )");
+    println!("join ( list separator -- s )");
+    println!("substr ( s start length -- s )");
+    println!("indexOf ( s part -- int )");
+    println!("contains ( s part -- bool )");
+    println!("startsWith endsWith ( s part -- bool )");
+    println!("toUpper toLower trim ( s -- s )");
+    println!("replace ( s from to -- s )");
+    println!("chars ( s -- list )");
+    println!("strlen ( s -- int )");
+    println!("repeat ( s n -- s )");
+    println!("format ( values template -- s )");
+    println!("take ( list n -- list )");
     println!("cons: ( item list -- list )");
     println!("append ( list1 list2 -- list3 ) ");
+    println!("get ( map key -- value )");
+    println!("put ( map key value -- map )");
+    println!("remove ( map key -- map )");
+    println!("has ( map key -- bool )");
+    println!("keys values entries ( map -- list )");
+    println!("size ( map -- int )");
     println!("map quotation ( list -- newlist )");
+    println!("filter quotation ( list -- newlist )");
     println!("each quotation ( list -- )");
     println!("foldl quotation ( list initial_accumulator -- final_accumulator )");
+    println!("foldr quotation ( list initial_accumulator -- final_accumulator )");
+    println!("any all quotation ( list -- bool )");
+    println!("find quotation ( list -- item )");
+    println!("sortBy quotation ( list -- newlist )");
+    println!("sort reverse uniq flatten ( list -- newlist )");
+    println!("nth ( list n -- item )");
+    println!("drop ( list n -- list )");
+    println!("slice ( list start end -- list )");
+    println!("zip ( list1 list2 -- pairs )");
+    println!("unzip ( pairs -- list1 list2 )");
+    println!("range ( start end step -- list )");
+    println!("sum product ( list -- number )");
+    println!("if true_block false_block ( bool -- )");
+    println!("times quotation ( n -- )");
+    println!("loop condition quotation ( -- )");
+    println!("while condition quotation ( -- )");
+    println!("fun name quotation - defines the word name");
 }
 
 //interactive/terminal/gchi like mode
-fn interactive_mode(stack: &mut Stack) {
+fn interactive_mode(interpreter: &mut Interpreter) {
     //is a loop where the user types in input and gets output right away until they quit
     loop {
         //to signify that we expect input
@@ -49,76 +128,57 @@ fn interactive_mode(stack: &mut Stack) {
         io::stdout().flush().unwrap();
 
         let mut input = String::new();
-        //reads the inputted line
-        io::stdin().read_line(&mut input).unwrap();
+        //reads the inputted line, stops when the input has ended
+        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            break;
+        }
         //trims everything around so we get the input and nothing outisde of it
         let input = input.trim();
 
         //implemented an QA functionality with some functions to explain more about usage, see current stack or quit
-        if input.starts_with("?") {
-            match &input[1..] {
+        if let Some(command) = input.strip_prefix('?') {
+            match command {
                 "h" => print_commands(),
-                "s" => stack.show(),
+                "s" => println!("Stack: {:?}", interpreter.stack()),
                 "q" => break,
                 _ => println!("Unknown command. Type ?h for help."),
             }
         } else {
             //else use the parse function to recognize the input
-            match stack.parse(input) {
-                Ok(()) => (),
-                Err(e) => eprintln!("Error: {}", e),
+            if let Err(e) = interpreter.eval_source("<stdin>", input) {
+                report(interpreter, &e);
             }
-            println!("Stack: {:?}", stack.data);
+            println!("Stack: {:?}", interpreter.stack());
         }
     }
 }
 
-//where the code is executed from a file
-fn execute_from_file(stack: &mut Stack, path: &str) -> Result<(), &'static str> {
-    let content = fs::read_to_string(path).map_err(|_| "Failed to read the file")?;
-    //splits the file by lines
-    let lines = content.split('\n');
+//writes the error through the interpreter's io, where the output of print also goes
+fn report(interpreter: &mut Interpreter, error: &Error) {
+    let _ = interpreter.io().write_err(&format!("Error: {}", error));
+}
 
-    //goes trough each line
-    for line in lines {
-        //trims the  line
-        let trimmed_line = line.trim();
-        //if the line is not empty use the parse function to recognize it
-        if !trimmed_line.is_empty() {
-            match stack.parse(trimmed_line) {
-                Ok(()) => (),
-                Err(e) => return Err(e),
-            }
-        }
-    }
-    Ok(())
+//where the code is executed from a file
+fn execute_from_file(interpreter: &mut Interpreter, path: &str) -> Result<(), Error> {
+    let content = fs::read_to_string(path).map_err(|e| Error::new(ErrorKind::IoError(format!("Failed to read the file: {}", e))))?;
+    //the whole file is parsed at once so the spans point at the right line
+    interpreter.eval_source(path, &content)
 }
 
 fn main() {
 
-    let v = vec![1,2];
-    let x: Option<&i32> = v.get(3);
-    match x {
-        Some(value) => println!("{value}"),
-        None => println!("No value at that place"),
-    }
-
-    //creates the stack we will operate on
-    let mut stack = Stack::new();
+    //creates the interpreter we will operate on
+    let mut interpreter = Interpreter::new();
     //number of arguments when starting th program
     let args: Vec<String> = env::args().collect();
 
     //if the arguments are more than 1 it is filemode because then we also have to declare a file
     if args.len() > 1 {
         let file_path = &args[1];
-        match execute_from_file(&mut stack, file_path) {
-            Ok(()) => (),
-            Err(e) => eprintln!("Error: {}", e),
+        if let Err(e) = execute_from_file(&mut interpreter, file_path) {
+            report(&mut interpreter, &e);
         }
     } else {
-        interactive_mode(&mut stack);
+        interactive_mode(&mut interpreter);
     }
 }
-
-#[cfg(test)]
-mod test;
\ No newline at end of file
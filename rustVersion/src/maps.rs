@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use crate::error::*;
+use crate::list::List;
+use crate::stack::*;
+use crate::stackvalues::*;
+
+//the map words, the map is below the key and value so they can be chained like {| |} "a" 1 put "b" 2 put
+
+fn pop_map(stack: &mut Stack, op: &str) -> Result<BTreeMap<Key, StackValue>, Error> {
+    match stack.pop_value()? {
+        StackValue::Map(map) => Ok(map),
+        other => Err(Error::type_mismatch(op, "a map", &[&other])),
+    }
+}
+
+fn pop_key(stack: &mut Stack, op: &str) -> Result<Key, Error> {
+    let key = stack.pop_value()?;
+    Key::from_value(&key).ok_or(Error::type_mismatch(op, "a bool, integer, string or symbol as the key", &[&key]))
+}
+
+//( map key -- value )
+pub fn get(stack: &mut Stack) -> Result<(), Error> {
+    let key = pop_key(stack, "get")?;
+    let map = pop_map(stack, "get")?;
+    match map.get(&key) {
+        Some(value) => {
+            stack.push(value.clone());
+            Ok(())
+        }
+        None => Err(Error::invalid(&format!("The key {:?} is not in the map", key))),
+    }
+}
+
+//( map key value -- map ), replaces the value if the key is already there
+pub fn put(stack: &mut Stack) -> Result<(), Error> {
+    let value = stack.pop_value()?;
+    let key = pop_key(stack, "put")?;
+    let mut map = pop_map(stack, "put")?;
+    map.insert(key, value);
+    stack.push(StackValue::Map(map));
+    Ok(())
+}
+
+//( map key -- map ), the map is left as it was if the key isn't there
+pub fn remove(stack: &mut Stack) -> Result<(), Error> {
+    let key = pop_key(stack, "remove")?;
+    let mut map = pop_map(stack, "remove")?;
+    map.remove(&key);
+    stack.push(StackValue::Map(map));
+    Ok(())
+}
+
+//( map key -- bool )
+pub fn has(stack: &mut Stack) -> Result<(), Error> {
+    let key = pop_key(stack, "has")?;
+    let map = pop_map(stack, "has")?;
+    stack.push(StackValue::Bool(map.contains_key(&key)));
+    Ok(())
+}
+
+//( map -- list )
+pub fn keys(stack: &mut Stack) -> Result<(), Error> {
+    let map = pop_map(stack, "keys")?;
+    stack.push(StackValue::Vec(map.keys().map(Key::to_value).collect()));
+    Ok(())
+}
+
+//( map -- list )
+pub fn values(stack: &mut Stack) -> Result<(), Error> {
+    let map = pop_map(stack, "values")?;
+    stack.push(StackValue::Vec(map.into_values().collect()));
+    Ok(())
+}
+
+//( map -- list ), a [key value] list for every entry
+pub fn entries(stack: &mut Stack) -> Result<(), Error> {
+    let map = pop_map(stack, "entries")?;
+    let entries = map.into_iter().map(|(key, value)| StackValue::Vec(List::from(vec![key.to_value(), value]))).collect();
+    stack.push(StackValue::Vec(entries));
+    Ok(())
+}
+
+//( map -- int )
+pub fn size(stack: &mut Stack) -> Result<(), Error> {
+    let map = pop_map(stack, "size")?;
+    stack.push(StackValue::Int(map.len() as i64));
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+use std::f64::consts;
+
+use crate::error::*;
+use crate::numeric::*;
+use crate::stack::*;
+use crate::stackvalues::*;
+
+//the math words, numbers are promoted the same way as for the arithmetic words
+
+fn is_number(value: &StackValue) -> bool {
+    to_f64(value).is_some()
+}
+
+//the remainder with the sign of the divisor, so -7 3 mod is 2
+pub fn modulo(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(_, 0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        Some(Numbers::Ints(x, y)) => {
+            let remainder = x.checked_rem(y).ok_or(Error::overflow("mod"))?;
+            if remainder != 0 && (remainder < 0) != (y < 0) {
+                Ok(StackValue::Int(remainder + y))
+            } else {
+                Ok(StackValue::Int(remainder))
+            }
+        }
+        Some(Numbers::Rationals(x, y)) => {
+            let quotient = Rational::from(x.checked_div(y)?.floor());
+            x.checked_sub(y.checked_mul(quotient)?).map(StackValue::Rational)
+        }
+        Some(Numbers::Floats(_, 0.0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Float(x - y * (x / y).floor())),
+        None => Err(Error::type_mismatch("mod", "two numbers", &[&left, &right])),
+    })
+}
+
+//the remainder with the sign of the dividend, so -7 3 rem is -1
+pub fn remainder(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(_, 0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        Some(Numbers::Ints(x, y)) => x.checked_rem(y).map(StackValue::Int).ok_or(Error::overflow("rem")),
+        Some(Numbers::Rationals(x, y)) => {
+            let quotient = Rational::from(x.checked_div(y)?.trunc());
+            x.checked_sub(y.checked_mul(quotient)?).map(StackValue::Rational)
+        }
+        Some(Numbers::Floats(_, 0.0)) => Err(Error::new(ErrorKind::DivisionByZero)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Float(x % y)),
+        None => Err(Error::type_mismatch("rem", "two numbers", &[&left, &right])),
+    })
+}
+
+pub fn abs(stack: &mut Stack) -> Result<(), Error> {
+    stack.unary_op(|value| match value {
+        StackValue::Int(x) => x.checked_abs().map(StackValue::Int).ok_or(Error::overflow("abs")),
+        StackValue::Rational(x) if x.numerator() < 0 => x.checked_neg().map(StackValue::Rational),
+        StackValue::Rational(x) => Ok(StackValue::Rational(x)),
+        StackValue::Float(x) => Ok(StackValue::Float(x.abs())),
+        value => Err(Error::type_mismatch("abs", "a number", &[&value])),
+    })
+}
+
+//min and max give back one of the values as it was, so 1 2.5 min is the int 1
+fn choose(stack: &mut Stack, op: &str, keep: Ordering) -> Result<(), Error> {
+    stack.binary_op(|left, right| {
+        if !is_number(&left) || !is_number(&right) {
+            return Err(Error::type_mismatch(op, "two numbers", &[&left, &right]));
+        }
+        match compare(&left, &right) {
+            Some(ordering) if ordering == keep || ordering == Ordering::Equal => Ok(left),
+            Some(_) => Ok(right),
+            //one of them is NaN
+            None => Ok(StackValue::Float(f64::NAN)),
+        }
+    })
+}
+
+pub fn min(stack: &mut Stack) -> Result<(), Error> {
+    choose(stack, "min", Ordering::Less)
+}
+
+pub fn max(stack: &mut Stack) -> Result<(), Error> {
+    choose(stack, "max", Ordering::Greater)
+}
+
+//ints to a negative power become rationals, anything with a float is a float
+pub fn pow(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (&left, &right) {
+        (StackValue::Int(x), StackValue::Int(y)) if *y >= 0 => int_pow(*x, *y).map(StackValue::Int),
+        (StackValue::Int(x), StackValue::Int(y)) => Rational::from(*x).checked_pow(*y).map(StackValue::Rational),
+        (StackValue::Rational(x), StackValue::Int(y)) => x.checked_pow(*y).map(StackValue::Rational),
+        _ => match (to_f64(&left), to_f64(&right)) {
+            (Some(x), Some(y)) => Ok(StackValue::Float(x.powf(y))),
+            _ => Err(Error::type_mismatch("pow", "two numbers", &[&left, &right])),
+        },
+    })
+}
+
+fn int_pow(base: i64, exponent: i64) -> Result<i64, Error> {
+    match u32::try_from(exponent) {
+        Ok(exponent) => base.checked_pow(exponent).ok_or(Error::overflow("pow")),
+        //only these stay small for exponents that large
+        Err(_) => match base {
+            0 | 1 => Ok(base),
+            -1 => Ok(if exponent % 2 == 0 { 1 } else { -1 }),
+            _ => Err(Error::overflow("pow")),
+        },
+    }
+}
+
+//the words that only make sense on floats, ints and rationals are made floats first
+fn float_function(stack: &mut Stack, op: &str, function: fn(f64) -> f64) -> Result<(), Error> {
+    stack.unary_op(|value| match to_f64(&value) {
+        Some(x) => Ok(StackValue::Float(function(x))),
+        None => Err(Error::type_mismatch(op, "a number", &[&value])),
+    })
+}
+
+pub fn sqrt(stack: &mut Stack) -> Result<(), Error> {
+    if matches!(stack.data.last().and_then(to_f64), Some(x) if x < 0.0) {
+        return Err(Error::invalid("Can't take the square root of a negative number"));
+    }
+    float_function(stack, "sqrt", f64::sqrt)
+}
+
+pub fn exp(stack: &mut Stack) -> Result<(), Error> {
+    float_function(stack, "exp", f64::exp)
+}
+
+//the natural logarithm
+pub fn log(stack: &mut Stack) -> Result<(), Error> {
+    if matches!(stack.data.last().and_then(to_f64), Some(x) if x <= 0.0) {
+        return Err(Error::invalid("Can only take the logarithm of a positive number"));
+    }
+    float_function(stack, "log", f64::ln)
+}
+
+pub fn sin(stack: &mut Stack) -> Result<(), Error> {
+    float_function(stack, "sin", f64::sin)
+}
+
+pub fn cos(stack: &mut Stack) -> Result<(), Error> {
+    float_function(stack, "cos", f64::cos)
+}
+
+pub fn tan(stack: &mut Stack) -> Result<(), Error> {
+    float_function(stack, "tan", f64::tan)
+}
+
+pub fn gcd_word(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Int(x), StackValue::Int(y)) => {
+            i64::try_from(gcd(x as i128, y as i128)).map(StackValue::Int).map_err(|_| Error::overflow("gcd"))
+        }
+        (left, right) => Err(Error::type_mismatch("gcd", "two integers", &[&left, &right])),
+    })
+}
+
+pub fn lcm(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match (left, right) {
+        (StackValue::Int(0), StackValue::Int(_)) | (StackValue::Int(_), StackValue::Int(0)) => Ok(StackValue::Int(0)),
+        (StackValue::Int(x), StackValue::Int(y)) => {
+            let lcm = (x as i128 * y as i128).abs() / gcd(x as i128, y as i128);
+            i64::try_from(lcm).map(StackValue::Int).map_err(|_| Error::overflow("lcm"))
+        }
+        (left, right) => Err(Error::type_mismatch("lcm", "two integers", &[&left, &right])),
+    })
+}
+
+pub fn pi(stack: &mut Stack) -> Result<(), Error> {
+    stack.push(StackValue::Float(consts::PI));
+    Ok(())
+}
+
+pub fn e(stack: &mut Stack) -> Result<(), Error> {
+    stack.push(StackValue::Float(consts::E));
+    Ok(())
+}
+
+pub fn less_or_equal(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(x, y)) => Ok(StackValue::Bool(x <= y)),
+        Some(Numbers::Rationals(x, y)) => Ok(StackValue::Bool(x <= y)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Bool(x <= y)),
+        None => Err(Error::type_mismatch("<=", "two numbers", &[&left, &right])),
+    })
+}
+
+pub fn greater_or_equal(stack: &mut Stack) -> Result<(), Error> {
+    stack.binary_op(|left, right| match promote(&left, &right) {
+        Some(Numbers::Ints(x, y)) => Ok(StackValue::Bool(x >= y)),
+        Some(Numbers::Rationals(x, y)) => Ok(StackValue::Bool(x >= y)),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Bool(x >= y)),
+        None => Err(Error::type_mismatch(">=", "two numbers", &[&left, &right])),
+    })
+}
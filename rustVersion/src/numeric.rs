@@ -0,0 +1,241 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::error::*;
+use crate::stackvalues::*;
+
+//two numbers brought to the same type so arithmetic and comparisons can work on them,
+//numbers are promoted along int -> rational -> float so an int with a float is a float
+pub enum Numbers {
+    Ints(i64, i64),
+    Rationals(Rational, Rational),
+    Floats(f64, f64),
+}
+
+//None when one of the values isn't a number
+pub fn promote(left: &StackValue, right: &StackValue) -> Option<Numbers> {
+    match (left, right) {
+        (StackValue::Int(x), StackValue::Int(y)) => Some(Numbers::Ints(*x, *y)),
+        (StackValue::Int(_) | StackValue::Rational(_), StackValue::Int(_) | StackValue::Rational(_)) => {
+            Some(Numbers::Rationals(to_rational(left)?, to_rational(right)?))
+        }
+        _ => Some(Numbers::Floats(to_f64(left)?, to_f64(right)?)),
+    }
+}
+
+//orders two numbers of any type, None when one isn't a number or a float is NaN
+pub fn compare(left: &StackValue, right: &StackValue) -> Option<Ordering> {
+    match promote(left, right)? {
+        Numbers::Ints(x, y) => x.partial_cmp(&y),
+        Numbers::Rationals(x, y) => x.partial_cmp(&y),
+        Numbers::Floats(x, y) => x.partial_cmp(&y),
+    }
+}
+
+//whether two values are ==, numbers are compared by value so 1 and 1.0 are equal, also when they are
+//inside lists or are the values of maps
+pub fn values_equal(left: &StackValue, right: &StackValue) -> bool {
+    match (left, right) {
+        (StackValue::Vec(x), StackValue::Vec(y)) => x.len() == y.len() && x.iter().zip(y).all(|(x, y)| values_equal(x, y)),
+        (StackValue::Map(x), StackValue::Map(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|((k, x), (l, y))| k == l && values_equal(x, y))
+        }
+        _ => match promote(left, right) {
+            Some(Numbers::Ints(x, y)) => x == y,
+            Some(Numbers::Rationals(x, y)) => x == y,
+            Some(Numbers::Floats(x, y)) => x == y,
+            None => left == right,
+        },
+    }
+}
+
+//the sum of two numbers, used by + and sum
+pub fn add_values(left: &StackValue, right: &StackValue) -> Result<StackValue, Error> {
+    match promote(left, right) {
+        Some(Numbers::Ints(x, y)) => x.checked_add(y).map(StackValue::Int).ok_or(Error::overflow("+")),
+        Some(Numbers::Rationals(x, y)) => x.checked_add(y).map(StackValue::Rational),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Float(x + y)),
+        None => Err(Error::type_mismatch("+", "two numbers", &[left, right])),
+    }
+}
+
+//the product of two numbers, used by * and product
+pub fn multiply_values(left: &StackValue, right: &StackValue) -> Result<StackValue, Error> {
+    match promote(left, right) {
+        Some(Numbers::Ints(x, y)) => x.checked_mul(y).map(StackValue::Int).ok_or(Error::overflow("*")),
+        Some(Numbers::Rationals(x, y)) => x.checked_mul(y).map(StackValue::Rational),
+        Some(Numbers::Floats(x, y)) => Ok(StackValue::Float(x * y)),
+        None => Err(Error::type_mismatch("*", "two numbers", &[left, right])),
+    }
+}
+
+pub fn to_f64(value: &StackValue) -> Option<f64> {
+    match value {
+        StackValue::Int(x) => Some(*x as f64),
+        StackValue::Rational(x) => Some(x.to_f64()),
+        StackValue::Float(x) => Some(*x),
+        _ => None,
+    }
+}
+
+fn to_rational(value: &StackValue) -> Option<Rational> {
+    match value {
+        StackValue::Int(x) => Some(Rational::from(*x)),
+        StackValue::Rational(x) => Some(*x),
+        _ => None,
+    }
+}
+
+//greatest common divisor, always positive unless both are zero
+pub fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+//an exact fraction, always kept with the smallest denominator possible and the sign in the numerator
+#[derive(Clone, Copy, PartialEq)]
+pub struct Rational {
+    numerator: i64,
+    denominator: i64,
+}
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Result<Self, Error> {
+        Rational::normalised(numerator as i128, denominator as i128, "/")
+    }
+
+    pub fn from(value: i64) -> Self {
+        Rational { numerator: value, denominator: 1 }
+    }
+
+    //the exact value of a float, which is a fraction with a power of two below it
+    pub fn from_f64(value: f64) -> Result<Self, Error> {
+        let mut denominator: i64 = 1;
+        while value * (denominator as f64) != (value * denominator as f64).trunc() {
+            if denominator == 1 << 62 {
+                return Err(Error::invalid(&format!("{} can't be made into a rational", value)));
+            }
+            denominator *= 2;
+        }
+        let numerator = value * denominator as f64;
+        if !numerator.is_finite() || numerator < i64::MIN as f64 || numerator >= i64::MAX as f64 {
+            return Err(Error::invalid(&format!("{} can't be made into a rational", value)));
+        }
+        Rational::new(numerator as i64, denominator)
+    }
+
+    //the calculations are done with 128 bits so only a result that doesn't fit is an overflow
+    fn normalised(numerator: i128, denominator: i128, op: &str) -> Result<Self, Error> {
+        if denominator == 0 {
+            return Err(Error::new(ErrorKind::DivisionByZero));
+        }
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+        let numerator = i64::try_from(numerator / divisor).map_err(|_| Error::overflow(op))?;
+        let denominator = i64::try_from(denominator / divisor).map_err(|_| Error::overflow(op))?;
+        Ok(Rational { numerator, denominator })
+    }
+
+    pub fn numerator(&self) -> i64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i64 {
+        self.denominator
+    }
+
+    fn parts(&self) -> (i128, i128) {
+        (self.numerator as i128, self.denominator as i128)
+    }
+
+    pub fn checked_add(self, other: Rational) -> Result<Rational, Error> {
+        let ((a, b), (c, d)) = (self.parts(), other.parts());
+        Rational::normalised(a * d + c * b, b * d, "+")
+    }
+
+    pub fn checked_sub(self, other: Rational) -> Result<Rational, Error> {
+        let ((a, b), (c, d)) = (self.parts(), other.parts());
+        Rational::normalised(a * d - c * b, b * d, "-")
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Result<Rational, Error> {
+        let ((a, b), (c, d)) = (self.parts(), other.parts());
+        Rational::normalised(a * c, b * d, "*")
+    }
+
+    pub fn checked_div(self, other: Rational) -> Result<Rational, Error> {
+        let ((a, b), (c, d)) = (self.parts(), other.parts());
+        Rational::normalised(a * d, b * c, "/")
+    }
+
+    pub fn checked_neg(self) -> Result<Rational, Error> {
+        let (a, b) = self.parts();
+        Rational::normalised(-a, b, "not")
+    }
+
+    //negative exponents give the power of the reciprocal
+    pub fn checked_pow(self, exponent: i64) -> Result<Rational, Error> {
+        let base = if exponent < 0 { Rational::from(1).checked_div(self)? } else { self };
+        let exponent = u32::try_from(exponent.unsigned_abs()).map_err(|_| Error::overflow("pow"))?;
+        let numerator = base.numerator.checked_pow(exponent).ok_or(Error::overflow("pow"))?;
+        let denominator = base.denominator.checked_pow(exponent).ok_or(Error::overflow("pow"))?;
+        Ok(Rational { numerator, denominator })
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    //the rounding of the fraction to whole numbers, these always fit as they are never further from zero than the numerator
+    pub fn trunc(&self) -> i64 {
+        self.numerator / self.denominator
+    }
+
+    pub fn floor(&self) -> i64 {
+        self.numerator.div_euclid(self.denominator)
+    }
+
+    pub fn ceil(&self) -> i64 {
+        -((-(self.numerator as i128)).div_euclid(self.denominator as i128) as i64)
+    }
+
+    //halves are rounded away from zero like f64::round
+    pub fn round(&self) -> i64 {
+        let (a, b) = self.parts();
+        let rounded = (2 * a.abs() + b) / (2 * b);
+        (rounded * a.signum()) as i64
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let ((a, b), (c, d)) = (self.parts(), other.parts());
+        //the denominators are positive so cross multiplying keeps the order
+        (a * d).partial_cmp(&(c * b))
+    }
+}
+
+//shown as numerator/denominator, e.g. 1/3
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+impl Debug for Rational {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+//the rounded float as an integer, it has to be a number that fits in one
+pub fn float_to_int(value: f64) -> Result<StackValue, Error> {
+    //2^63 itself doesn't fit, but every float below it does
+    if value.is_finite() && value >= i64::MIN as f64 && value < i64::MAX as f64 {
+        Ok(StackValue::Int(value as i64))
+    } else {
+        Err(Error::invalid(&format!("{} can't be made into an integer", value)))
+    }
+}
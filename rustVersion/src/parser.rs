@@ -0,0 +1,294 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+use crate::error::*;
+use crate::lexer::*;
+use crate::stackvalues::*;
+
+//a piece of a parsed program together with where it was written
+#[derive(Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Span,
+}
+
+//the different things a program is built from
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    //numbers, strings, bools, symbols and list ranges are pushed as they are
+    Literal(StackValue),
+    //[ ] list of literal elements
+    List(Vec<Node>),
+    //( ) tuple of literal elements that don't have to be of the same type
+    Tuple(Vec<Node>),
+    //{| |} map of keys and values, written after each other
+    Map(Vec<Node>),
+    //{ } block of code that is pushed as a quotation
+    Quotation(Vec<Node>),
+    //a call to a built-in or a variable
+    Word(String),
+    //cond if { true block } { false block }
+    If(Vec<Node>, Vec<Node>),
+    //:= assigns the value on top of the stack to the symbol below it
+    Assign,
+    //fun name { body } defines a word that runs the body when it is called
+    Fun(String, Vec<Node>),
+    //words like map that take the quotations written after them, e.g. map { 10 * } or loop { cond } { body }
+    Apply(String, Vec<Vec<Node>>),
+}
+
+//two nodes are the same if they do the same thing, no matter where they were written
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+//only the kind is shown so printing the stack doesn't get cluttered by spans
+impl Debug for Node {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.kind)
+    }
+}
+
+//writes a block of nodes between braces
+fn write_block(f: &mut Formatter, nodes: &[Node]) -> fmt::Result {
+    let block = nodes.iter().map(|node| node.to_string()).collect::<Vec<String>>().join(" ");
+    write!(f, "{{{}}}", block)
+}
+
+//shows the node the way it would be written in the source
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match &self.kind {
+            //escaped so the string can be read back in
+            NodeKind::Literal(StackValue::String(value)) => write!(f, "{:?}", value),
+            NodeKind::Literal(StackValue::Symbol(value)) => write!(f, ":{}", value),
+            NodeKind::Literal(StackValue::Rational(value)) => write!(f, "{}r", value),
+            NodeKind::Literal(value) => write!(f, "{}", value),
+            NodeKind::List(elements) => {
+                let list = elements.iter().map(|node| node.to_string()).collect::<Vec<String>>().join(", ");
+                write!(f, "[{}]", list)
+            }
+            NodeKind::Tuple(elements) => {
+                let tuple = elements.iter().map(|node| node.to_string()).collect::<Vec<String>>().join(", ");
+                write!(f, "({})", tuple)
+            }
+            NodeKind::Map(elements) => {
+                let map = elements.iter().map(|node| node.to_string()).collect::<Vec<String>>().join(" ");
+                write!(f, "{{| {} |}}", map)
+            }
+            NodeKind::Quotation(body) => write_block(f, body),
+            NodeKind::Word(word) => write!(f, "{}", word),
+            NodeKind::If(true_block, false_block) => {
+                write!(f, "if ")?;
+                write_block(f, true_block)?;
+                write!(f, " ")?;
+                write_block(f, false_block)
+            }
+            NodeKind::Assign => write!(f, ":="),
+            NodeKind::Fun(name, body) => {
+                write!(f, "fun {} ", name)?;
+                write_block(f, body)
+            }
+            NodeKind::Apply(word, blocks) => {
+                write!(f, "{}", word)?;
+                for block in blocks {
+                    write!(f, " ")?;
+                    write_block(f, block)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+//method to interpret individual words such that true is the bool true
+pub fn word_value(input: &str) -> Result<StackValue, Error> {
+    if input.eq_ignore_ascii_case("true") {
+        Ok(StackValue::Bool(true))
+    } else if input.eq_ignore_ascii_case("false") {
+        Ok(StackValue::Bool(false))
+    } else if input.len() == 1 {
+        Ok(StackValue::Symbol(input.to_string()))
+    } else {
+        Err(Error::new(ErrorKind::UnknownWord(input.to_string())))
+    }
+}
+
+//error pointing at the token at index, or at the last token when the source ended before it
+fn error_at(tokens: &[Token], index: usize, message: &str) -> Error {
+    let token = tokens.get(index).unwrap_or(&tokens[tokens.len() - 1]);
+    Error::parse(message).at(&token.span)
+}
+
+//how deep quotations, lists, tuples and maps can be inside each other. Parsing, compiling, printing and freeing
+//them goes into each level on the rust stack, so deeper nesting is an error instead of running out of it, even
+//on the small stacks of threads other than the main one
+const MAX_NESTING: usize = 100;
+const TOO_DEEP: &str = "Quotations and lists are nested too deeply";
+
+//turns the tokens of a whole program into nodes
+pub fn parse_program(tokens: &[Token]) -> Result<Vec<Node>, Error> {
+    let mut index = 0;
+    let mut nodes = Vec::new();
+    while index < tokens.len() {
+        let node = parse_node(tokens, &mut index, 0, follows_quotation(&nodes))?;
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+//parses the nodes of a block up to its closing brace and moves the index past it
+fn parse_block(tokens: &[Token], index: &mut usize, depth: usize) -> Result<Vec<Node>, Error> {
+    let mut nodes = Vec::new();
+    //the lexer has made sure the closing brace is there
+    while tokens[*index].kind != TokenKind::RBrace {
+        let node = parse_node(tokens, index, depth, follows_quotation(&nodes))?;
+        nodes.push(node);
+    }
+    *index += 1;
+    Ok(nodes)
+}
+
+//whether the last node parsed is a quotation literal, a word like map or if written right after one uses the
+//quotations on the stack, so a block after the word is just pushed, e.g. [1 2] { 1 + } map { length } exec
+fn follows_quotation(nodes: &[Node]) -> bool {
+    matches!(nodes.last(), Some(Node { kind: NodeKind::Quotation(_), .. }))
+}
+
+//parses the { } block a word like if needs after it
+fn parse_required_block(tokens: &[Token], index: &mut usize, depth: usize, error: &str) -> Result<Vec<Node>, Error> {
+    match tokens.get(*index) {
+        Some(Token { kind: TokenKind::LBrace, .. }) => {
+            *index += 1;
+            parse_block(tokens, index, depth + 1)
+        }
+        _ => Err(error_at(tokens, *index, error)),
+    }
+}
+
+//parses the node starting at index and moves the index past it, depth is how many blocks it is inside
+fn parse_node(tokens: &[Token], index: &mut usize, depth: usize, after_quotation: bool) -> Result<Node, Error> {
+    if depth > MAX_NESTING {
+        return Err(error_at(tokens, *index, TOO_DEEP));
+    }
+    let token = &tokens[*index];
+    *index += 1;
+    let block_follows = !after_quotation && tokens.get(*index).map(|next| &next.kind) == Some(&TokenKind::LBrace);
+    let kind = match &token.kind {
+        TokenKind::Word(word) => match word.as_str() {
+            //without blocks written after it, if uses the two quotations on the stack, e.g. { 1 } { 2 } if
+            "if" if block_follows => {
+                let true_block = parse_required_block(tokens, index, depth, "Expected a '{' block after 'if'")?;
+                let false_block = parse_required_block(tokens, index, depth, "Expected a '{' block after 'if'")?;
+                NodeKind::If(true_block, false_block)
+            }
+            "fun" => {
+                let name = match tokens.get(*index).map(|next| &next.kind) {
+                    Some(TokenKind::Word(name)) => name.clone(),
+                    _ => return Err(error_at(tokens, *index, "Expected a name after 'fun'")),
+                };
+                *index += 1;
+                let body = parse_required_block(tokens, index, depth, "Expected a '{' block after the name of the word")?;
+                NodeKind::Fun(name, body)
+            }
+            ":=" => NodeKind::Assign,
+            //push just pushes the value after it
+            "push" => {
+                if *index < tokens.len() {
+                    return parse_node(tokens, index, depth + 1, false);
+                }
+                return Err(error_at(tokens, *index - 1, "Missing value after 'push'"));
+            }
+            //without quotations written after them they use the ones on the stack, e.g. { 1 + } map
+            "map" | "filter" | "each" | "foldl" | "foldr" | "sortBy" | "any" | "all" | "find" | "times" | "loop" | "while" if block_follows => {
+                //loop and while take a condition block and a body block
+                let count = if word == "loop" || word == "while" { 2 } else { 1 };
+                let mut blocks = Vec::new();
+                for _ in 0..count {
+                    blocks.push(parse_required_block(tokens, index, depth, "Expected a '{' block after the word")?);
+                }
+                NodeKind::Apply(word.clone(), blocks)
+            }
+            _ if word.eq_ignore_ascii_case("true") || word.eq_ignore_ascii_case("false") => {
+                NodeKind::Literal(word_value(word)?)
+            }
+            _ => NodeKind::Word(word.clone()),
+        },
+        //everything else is a literal
+        _ => {
+            *index -= 1;
+            return parse_element(tokens, index, depth);
+        }
+    };
+    Ok(Node { kind, span: token.span.clone() })
+}
+
+//parses a literal, list or quotation at index and moves the index past it
+fn parse_element(tokens: &[Token], index: &mut usize, depth: usize) -> Result<Node, Error> {
+    if depth > MAX_NESTING {
+        return Err(error_at(tokens, *index, TOO_DEEP));
+    }
+    let token = &tokens[*index];
+    *index += 1;
+    let kind = match &token.kind {
+        TokenKind::Int(value) => NodeKind::Literal(StackValue::Int(*value)),
+        TokenKind::Float(value) => NodeKind::Literal(StackValue::Float(*value)),
+        TokenKind::Rational(value) => NodeKind::Literal(StackValue::Rational(*value)),
+        TokenKind::String(value) => NodeKind::Literal(StackValue::String(value.clone())),
+        TokenKind::Symbol(symbol) => NodeKind::Literal(StackValue::Symbol(symbol.clone())),
+        TokenKind::Word(word) => NodeKind::Literal(word_value(word).map_err(|e| e.at(&token.span))?),
+        TokenKind::LBracket => parse_list(tokens, index, depth + 1)?,
+        TokenKind::LBrace => NodeKind::Quotation(parse_block(tokens, index, depth + 1)?),
+        TokenKind::LMap => {
+            let mut elements = Vec::new();
+            //the lexer has made sure the |} is there
+            while tokens[*index].kind != TokenKind::RMap {
+                elements.push(parse_element(tokens, index, depth + 1)?);
+            }
+            *index += 1;
+            NodeKind::Map(elements)
+        }
+        TokenKind::LParen => {
+            let mut elements = Vec::new();
+            //the lexer has made sure the ) is there
+            while tokens[*index].kind != TokenKind::RParen {
+                elements.push(parse_element(tokens, index, depth + 1)?);
+            }
+            *index += 1;
+            NodeKind::Tuple(elements)
+        }
+        TokenKind::RBracket | TokenKind::RBrace | TokenKind::RMap | TokenKind::RParen => return Err(Error::parse("Unexpected closing bracket").at(&token.span)),
+        TokenKind::DotDot => return Err(Error::parse("Unexpected '..' outside of a list range").at(&token.span)),
+    };
+    Ok(Node { kind, span: token.span.clone() })
+}
+
+//parses a list, the lexer has made sure the closing bracket is there
+fn parse_list(tokens: &[Token], index: &mut usize, depth: usize) -> Result<NodeKind, Error> {
+    let mut elements = Vec::new();
+    while tokens[*index].kind != TokenKind::RBracket {
+        //[a..b] is a list range and [a..] an infinite list, the start is the element read before the ..
+        if tokens[*index].kind == TokenKind::DotDot {
+            let range_error = error_at(tokens, *index, "Invalid range list format");
+            *index += 1;
+            let start = match elements.as_slice() {
+                [Node { kind: NodeKind::Literal(StackValue::Int(start)), .. }] => *start,
+                _ => return Err(range_error),
+            };
+            if tokens[*index].kind == TokenKind::RBracket {
+                *index += 1;
+                return Ok(NodeKind::Literal(StackValue::Lazy(Sequence::from(start))));
+            }
+            let end = match parse_element(tokens, index, depth)?.kind {
+                NodeKind::Literal(StackValue::Int(end)) if tokens[*index].kind == TokenKind::RBracket => end,
+                _ => return Err(range_error),
+            };
+            *index += 1;
+            return Ok(NodeKind::Literal(StackValue::Vec((start..=end).map(StackValue::Int).collect())));
+        }
+        elements.push(parse_element(tokens, index, depth)?);
+    }
+    *index += 1;
+    Ok(NodeKind::List(elements))
+}
@@ -1,30 +1,87 @@
-use std::io::{self, BufRead};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 pub struct Stack {
     pub data: Vec<StackValue>,
-    symbols: HashMap<String, StackValue>,
+    pub(crate) symbols: HashMap<String, StackValue>,
+    //words registered from rust, with how many values they take from the stack
+    natives: HashMap<String, (usize, NativeWord)>,
+    //where print and read go
+    pub(crate) io: Box<dyn Io>,
+    //how many runs of the virtual machine are inside each other, built-ins like map run their quotation in a new one
+    nested_runs: usize,
 }
 
+use crate::builtins::*;
+use crate::bytecode::*;
+use crate::error::*;
+use crate::io::*;
+use crate::lexer::*;
+use crate::list::*;
+use crate::parser::*;
 use crate::stackvalues::*;
 
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 //implemitation of the stack
 impl Stack {
     //creates a new Stack instance
     pub fn new() -> Self {
+        Self::with_io(StdIo)
+    }
+
+    //a stack that prints and reads through the given io instead of the terminal
+    pub fn with_io<I: Io + 'static>(io: I) -> Self {
         Stack {
             data: Vec::new(),
             symbols: HashMap::new(),
+            natives: HashMap::new(),
+            io: Box::new(io),
+            nested_runs: 0,
         }
     }
 
+    //registers a rust function as a word, arity is how many values it takes from the top of the stack
+    pub fn register_word<F>(&mut self, name: &str, arity: usize, word: F) -> Result<(), Error>
+    where
+        F: Fn(&mut Stack) -> Result<(), Error> + 'static,
+    {
+        if builtin_index(name).is_some() {
+            return Err(Error::new(ErrorKind::RedefineBuiltin(name.to_string())));
+        }
+        self.natives.insert(name.to_string(), (arity, Rc::new(word)));
+        Ok(())
+    }
+
+    //built-in and registered words are looked up before symbols so they can't be assigned to
+    pub(crate) fn is_reserved(&self, name: &str) -> bool {
+        builtin_index(name).is_some() || self.natives.contains_key(name)
+    }
+
+    //runs a registered word, it has to get the values it declared and can't take more than those
+    fn call_native(&mut self, name: &str, arity: usize, word: &NativeWord) -> Result<(), Error> {
+        if self.data.len() < arity {
+            return Err(Error::new(ErrorKind::StackUnderflow));
+        }
+        let untouched = self.data.len() - arity;
+        word(self)?;
+        if self.data.len() < untouched {
+            return Err(Error::invalid(&format!("'{}' took more than its {} values from the stack", name, arity)));
+        }
+        Ok(())
+    }
+
     //operations on the stack (functionality being done on top elements)
-    fn binary_op<F>(&mut self, op: F) -> Result<(), &'static str>
+    pub fn binary_op<F>(&mut self, op: F) -> Result<(), Error>
     where
-        F: Fn(StackValue, StackValue) -> Result<StackValue, &'static str>,
+        F: Fn(StackValue, StackValue) -> Result<StackValue, Error>,
     {
         //must be atleast two
         if self.data.len() < 2 {
-            return Err("Not enough elements in the stack");
+            return Err(Error::new(ErrorKind::StackUnderflow));
         }
 
         let right = self.pop().unwrap();
@@ -39,39 +96,46 @@ impl Stack {
         }
     }
 
+    //pops one value and pushes the result of the operation on it
+    pub fn unary_op<F>(&mut self, op: F) -> Result<(), Error>
+    where
+        F: Fn(StackValue) -> Result<StackValue, Error>,
+    {
+        let value = self.pop_value()?;
+        self.push(op(value)?);
+        Ok(())
+    }
+
+    //pops the top value, it is an error if the stack is empty
+    pub fn pop_value(&mut self) -> Result<StackValue, Error> {
+        self.pop().ok_or(Error::new(ErrorKind::StackUnderflow))
+    }
+
     //assignment of variable
-    fn assignment(&mut self) -> Result<(), &'static str> {
-        if let Some(rhs) = self.pop() {
-            if let Some(lhs) = self.pop() {
-                //must be a symbol before the actual value
-                if let StackValue::Symbol(symbol) = lhs {
-                    self.symbols.insert(symbol, rhs);
-                    Ok(())
-                } else {
-                    Err("Left hand side of assignment must be a symbol")
-                }
-            } else {
-                Err("Stack is empty")
+    pub(crate) fn assignment(&mut self) -> Result<(), Error> {
+        let rhs = self.pop_value()?;
+        let lhs = self.pop_value()?;
+        //must be a symbol before the actual value
+        if let StackValue::Symbol(symbol) = lhs {
+            //built-ins are called before symbols are looked up so they can't be redefined
+            if self.is_reserved(&symbol) {
+                return Err(Error::new(ErrorKind::RedefineBuiltin(symbol)));
             }
+            self.symbols.insert(symbol, rhs);
+            Ok(())
         } else {
-            Err("Stack is empty")
+            Err(Error::type_mismatch(":=", "a symbol on the left hand side", &[&lhs]))
         }
     }
 
-    //inserts a symbol to a value
-    fn assign(&mut self, symbol: &str, value: StackValue) -> Result<(), &'static str> {
-        self.symbols.insert(symbol.to_string(), value);
-        Ok(())
-    }
-
     //pushes an element onto the stack
     pub fn push(&mut self, value: StackValue) {
         self.data.push(value);
     }
 
     //prints the stack
-    pub fn show(&self) {
-        println!("Stack: {:?}", self.data);
+    pub fn show(&mut self) {
+        let _ = self.io.write_line(&format!("Stack: {:?}", self.data));
     }
 
     //pops an element from the stack
@@ -80,407 +144,499 @@ impl Stack {
     }
 
     //duplicates the top element of the stack by cloing and pushing it
-    fn dup(&mut self) -> Result<(), &'static str> {
+    pub(crate) fn dup(&mut self) -> Result<(), Error> {
         if let Some(top) = self.data.last() {
             self.data.push(top.clone());
             Ok(())
         } else {
-            Err("Stack is empty")
+            Err(Error::new(ErrorKind::StackUnderflow))
         }
     }
 
     //swap the two top elements on the stack
-    fn swap(&mut self) -> Result<(), &'static str> {
+    pub(crate) fn swap(&mut self) -> Result<(), Error> {
         let len = self.data.len();
         if len < 2 {
-            return Err("Not enough elements in the stack");
+            return Err(Error::new(ErrorKind::StackUnderflow));
         }
         self.data.swap(len - 1, len - 2);
         Ok(())
     }
 
-    //pops and prints the top element from the stack 
-    fn print(&mut self) -> Result<(), &'static str> {
-        if let Some(top) = self.pop() {
-            println!("{}", top);
-            Ok(())
-        } else {
-            Err("Stack is empty")
+    //underflow unless there are at least count values on the stack
+    fn require(&self, count: usize) -> Result<(), Error> {
+        if self.data.len() < count {
+            return Err(Error::new(ErrorKind::StackUnderflow));
         }
+        Ok(())
+    }
+
+    //( a b -- a b a )
+    pub(crate) fn over(&mut self) -> Result<(), Error> {
+        self.require(2)?;
+        self.data.push(self.data[self.data.len() - 2].clone());
+        Ok(())
     }
 
-    //read a line from standard input and push it onto the stack
-    fn read(&mut self) -> io::Result<()> {
-        let stdin = io::stdin();
-        let mut line = String::new();
-        stdin.lock().read_line(&mut line)?;
-        self.push(StackValue::String(line.trim_end().to_string()));
+    //( a b c -- b c a )
+    pub(crate) fn rot(&mut self) -> Result<(), Error> {
+        self.require(3)?;
+        let len = self.data.len();
+        self.data[len - 3..].rotate_left(1);
         Ok(())
     }
 
+    //( a b c -- c a b )
+    pub(crate) fn minus_rot(&mut self) -> Result<(), Error> {
+        self.require(3)?;
+        let len = self.data.len();
+        self.data[len - 3..].rotate_right(1);
+        Ok(())
+    }
+
+    //( a b -- b )
+    pub(crate) fn nip(&mut self) -> Result<(), Error> {
+        self.require(2)?;
+        self.data.remove(self.data.len() - 2);
+        Ok(())
+    }
+
+    //( a b -- b a b )
+    pub(crate) fn tuck(&mut self) -> Result<(), Error> {
+        self.require(2)?;
+        self.data.insert(self.data.len() - 2, self.data[self.data.len() - 1].clone());
+        Ok(())
+    }
+
+    //( a b -- a b a b )
+    pub(crate) fn two_dup(&mut self) -> Result<(), Error> {
+        self.require(2)?;
+        self.data.extend_from_within(self.data.len() - 2..);
+        Ok(())
+    }
+
+    //( a b -- )
+    pub(crate) fn two_drop(&mut self) -> Result<(), Error> {
+        self.require(2)?;
+        self.data.truncate(self.data.len() - 2);
+        Ok(())
+    }
+
+    //( ... n -- ... x ) copies the value n below the top to the top, 0 pick is dup and 1 pick is over
+    pub(crate) fn pick(&mut self) -> Result<(), Error> {
+        let depth = self.pop_count("pick")?;
+        self.require(depth + 1)?;
+        self.data.push(self.data[self.data.len() - 1 - depth].clone());
+        Ok(())
+    }
+
+    //( ... n -- ... ) moves the value n below the top to the top, 1 roll is swap and 2 roll is rot
+    pub(crate) fn roll(&mut self) -> Result<(), Error> {
+        let depth = self.pop_count("roll")?;
+        self.require(depth + 1)?;
+        let value = self.data.remove(self.data.len() - 1 - depth);
+        self.data.push(value);
+        Ok(())
+    }
+
+    //( -- n ) the number of values on the stack
+    pub(crate) fn depth(&mut self) -> Result<(), Error> {
+        self.push(StackValue::Int(self.data.len() as i64));
+        Ok(())
+    }
+
+    //( ... -- ) empties the stack
+    pub(crate) fn clear(&mut self) -> Result<(), Error> {
+        self.data.clear();
+        Ok(())
+    }
+
+    //pops and prints the top element from the stack 
+    pub(crate) fn print(&mut self) -> Result<(), Error> {
+        let top = self.pop_value()?;
+        self.io.write_line(&top.to_string()).map_err(|e| Error::new(ErrorKind::IoError(format!("Failed to print: {}", e))))
+    }
+
+    //read a line from the input and push it onto the stack
+    pub(crate) fn read(&mut self) -> Result<(), Error> {
+        match self.io.read_line() {
+            Ok(Some(line)) => {
+                self.push(StackValue::String(line));
+                Ok(())
+            }
+            Ok(None) => Err(Error::new(ErrorKind::IoError("There is no more input to read".to_string()))),
+            Err(e) => Err(Error::new(ErrorKind::IoError(format!("Failed to read: {}", e)))),
+        }
+    }
+
     //appends the item in front of the list so item + list = list
-    fn cons(&mut self) -> Result<(), &'static str> {
+    pub(crate) fn cons(&mut self) -> Result<(), Error> {
         //have to be two values present
         if self.data.len() < 2 {
-            return Err("Not enough elements in the stack");
+            return Err(Error::new(ErrorKind::StackUnderflow));
         }
 
         let list = self.pop().unwrap();
         let item = self.pop().unwrap();
 
         match list {
-            StackValue::Vec(mut vec) => {
-                //inserts at index 0
-                vec.insert(0, item);
-                self.push(StackValue::Vec(vec));
+            StackValue::Vec(list) => {
+                //puts the item in front without copying the list
+                self.push(StackValue::Vec(list.cons(item)));
                 Ok(())
             }
-            _ => Err("The value is not a list"),
+            list => Err(Error::type_mismatch("cons", "a list", &[&list])),
         }
     }
 
     //concatenates two lists
-    fn append(&mut self) -> Result<(), &'static str> {
+    pub(crate) fn append(&mut self) -> Result<(), Error> {
         if self.data.len() < 2 {
-            return Err("Not enough elements in the stack");
+            return Err(Error::new(ErrorKind::StackUnderflow));
         }
 
         let list1 = self.pop().unwrap();
         let list2 = self.pop().unwrap();
 
         match (list1, list2) {
-            (StackValue::Vec(mut vec1), StackValue::Vec(vec2)) => {
-                //extends vec1 with vec2 and pushes vec1 to the new vec
-                vec1.extend(vec2);
-                self.push(StackValue::Vec(vec1));
+            (StackValue::Vec(vec1), StackValue::Vec(vec2)) => {
+                //vec1 followed by vec2, the values of vec2 are reused if nothing else shares them
+                self.push(StackValue::Vec(vec1.append(vec2)));
                 Ok(())
             }
-            _ => Err("Both values must be lists"),
-        }
-    }
-
-    //method to interpret individual values such that true is the bool true, things between "" is string etc.
-    fn parse_single_value(&self, input: &str) -> Result<StackValue, &'static str> {
-        if let Ok(int_value) = input.parse::<i32>() {
-            Ok(StackValue::Int(int_value))
-        } else if input.eq_ignore_ascii_case("true") {
-            Ok(StackValue::Bool(true))
-        } else if input.eq_ignore_ascii_case("false") {
-            Ok(StackValue::Bool(false))
-        } else if input.starts_with("'") && input.ends_with("'") {
-            Ok(StackValue::Symbol(input[1..input.len() - 1].to_string()))
-        } else if input.starts_with('"') && input.ends_with('"') {
-            Ok(StackValue::String(input[1..input.len() - 1].to_string()))
-        } else if input.len() == 1 {
-            Ok(StackValue::Symbol(input.to_string()))
-        } else {
-            println!("Unrecognized input: {}", input);
-            Err("Unrecognizable type")
+            (list1, list2) => Err(Error::type_mismatch("append", "two lists", &[&list2, &list1])),
         }
     }
 
-    //matches values (not method calls like the parse method but other characters)
-    fn parse_value(&self, input: &str) -> Result<StackValue, &'static str> {
-        //enclosed by square brackets with .. in is list ranges
-        if input.starts_with('[') && input.contains("..") && input.ends_with(']') {
-            //splits one the sides of the .. and checks if there two 
-            let range_parts: Vec<&str> = input[1..input.len() - 1].split("..").collect();
-            if range_parts.len() != 2 {
-                return Err("Invalid range list format");
-            }
-        
-            let start_value_str = range_parts[0].trim();
-            let end_value_str = range_parts[1].trim();
-        
-            //recursivle call to find out what each of them is
-            let start_value = self.parse_value(start_value_str)?;
-            let end_value = self.parse_value(end_value_str)?;
-        
-            //if int's creates a list from start to end
-            let elements = match (start_value, end_value) {
-                (StackValue::Int(start), StackValue::Int(end)) => {
-                    (start..=end).map(StackValue::Int).collect::<Vec<_>>()
-                }
-                _ => return Err("Invalid range list format"),
-            };
-            Ok(StackValue::Vec(elements))
-        } else if let Ok(int_value) = input.parse::<i32>() {
-            Ok(StackValue::Int(int_value))
-        //possibility of writing float with , and .
-        } else if let Ok(float_value) = input.replace(',', ".").parse::<f64>() { 
-            Ok(StackValue::Float(float_value))
-        } else if input.eq_ignore_ascii_case("true") {
-            Ok(StackValue::Bool(true))
-        } else if input.eq_ignore_ascii_case("false") {
-            Ok(StackValue::Bool(false))
-        //suqare brackets without .. is normal list
-        } else if input.starts_with('[') && input.ends_with(']') {
-            let elements_str = &input[1..input.len() - 1];
-            let elements_result: Result<Vec<StackValue>, &str> = elements_str
-                //design choice of me to split them by commas as a find this more concise 
-                .split(',')
-                //trims and parses each value in the list
-                .map(|s| self.parse_value(s.trim()))
-                .collect();
-        
-            match elements_result {
-                //have to be of same type
-                Ok(elements) => {
-                    if elements.windows(2).all(|w| w[0].same_type(&w[1])) {
-                        Ok(StackValue::Vec(elements))
-                    } else {
-                        Err("Lists with mixed types are not allowed")
-                    }
-                }
-                Err(e) => Err(e),
-            }
-        }  //symbol starts with : //quotation is between { }
-        else if input.starts_with('{') && input.ends_with('}') {
-            let quotation_str = input.trim_start_matches('{').trim_end_matches('}');
-            //also split by commas to stick with the design choice 
-            let tokens = quotation_str.split(',');
-            self.parse_quotation(tokens)
-        } else if input.starts_with('"') && input.ends_with('"') {
-            Ok(StackValue::String(input[1..input.len() - 1].to_string()))
-        } else {
-            //if not a group parsing as in this method it is most likely a single value so call that method
-            self.parse_single_value(input)
-        }
-    }
-
-    //parses quotation
-    fn parse_quotation<'a, I>(&self, tokens: I) -> Result<StackValue, &'static str>
-    where
-        I: Iterator<Item = &'a str>,
-    {
-        //divides the tokens given and parts them into StackValue types
-        let tokens: Vec<String> = tokens.map(|s| s.to_string()).collect();
-
-        let stack_values = tokens
-            .iter()
-            .map(|token| self.parse_value(token))
-            .collect::<Result<Vec<StackValue>, _>>()?;
-
-        Ok(StackValue::Quotation(stack_values))
-    }
-
     //from string to int if possible, if number in string format
-    fn parse_integer(self: &mut Stack) -> Result<(), &'static str> {
-        if let Some(StackValue::String(s)) = self.data.pop() {
-            match s.parse::<i32>() {
-                Ok(i) => {
+    pub(crate) fn parse_integer(self: &mut Stack) -> Result<(), Error> {
+        match self.pop_value()? {
+            StackValue::String(s) => match parse_int(&s) {
+                Some(i) => {
                     self.data.push(StackValue::Int(i));
                     Ok(())
                 }
-                Err(_) => Err("Failed to parse integer"),
-            }
-        } else {
-            Err("Stack is empty or top value is not a Text")
+                None => Err(Error::invalid(&format!("Failed to parse integer from \"{}\"", s))),
+            },
+            other => Err(Error::type_mismatch("parseInteger", "a string", &[&other])),
         }
     }
     
     //from stirng to float if possible, if number with . or ,
-    fn parse_float(self: &mut Stack) -> Result<(), &'static str> {
-        if let Some(StackValue::String(s)) = self.data.pop() {
-            match s.parse::<f64>() {
+    pub(crate) fn parse_float(self: &mut Stack) -> Result<(), Error> {
+        match self.pop_value()? {
+            StackValue::String(s) => match s.parse::<f64>() {
                 Ok(f) => {
                     self.data.push(StackValue::Float(f));
                     Ok(())
                 }
-                Err(_) => Err("Failed to parse float"),
-            }
-        } else {
-            Err("Stack is empty or top value is not a Text")
+                Err(_) => Err(Error::invalid(&format!("Failed to parse float from \"{}\"", s))),
+            },
+            other => Err(Error::type_mismatch("parseFloat", "a string", &[&other])),
         }
     }
     
     //from string to words
-    fn words(self: &mut Stack) -> Result<(), &'static str> {
-        if let Some(StackValue::String(s)) = self.data.pop() {
-            let tokens: Vec<StackValue> = s.split_whitespace().map(|word| StackValue::String(word.to_string())).collect();
-            self.data.push(StackValue::Vec(tokens));
-            Ok(())
-        } else {
-            Err("Stack is empty or top value is not a Text")
+    pub(crate) fn words(self: &mut Stack) -> Result<(), Error> {
+        match self.pop_value()? {
+            StackValue::String(s) => {
+                let tokens: List = s.split_whitespace().map(|word| StackValue::String(word.to_string())).collect();
+                self.data.push(StackValue::Vec(tokens));
+                Ok(())
+            }
+            other => Err(Error::type_mismatch("words", "a string", &[&other])),
         }
     }    
     
     //exec method to execute a quotation from the stack
-    fn exec(&mut self) -> Result<(), &'static str> {
-        if let Some(StackValue::Quotation(quotation)) = self.pop() {
-            for stack_value in quotation {
-                match stack_value {
-                    StackValue::Symbol(ref symbol) => {
-                        //handles symbols by executing commands based on symbol string
-                        self.parse(symbol)?;
-                    }
-                    //pushes everything else as their type
-                    StackValue::Int(value) => self.push(StackValue::Int(value)),
-                    StackValue::Float(value) => self.push(StackValue::Float(value)),
-                    StackValue::Bool(value) => self.push(StackValue::Bool(value)),
-                    StackValue::String(value) => self.push(StackValue::String(value)),
-                    StackValue::Vec(value) => self.push(StackValue::Vec(value)),
-                    _ => return Err("Invalid StackValue in Quotation"),
-                }
-            }
-            Ok(())
-        } else {
-            Err("Expected a Vec on the stack")
+    pub(crate) fn exec(&mut self) -> Result<(), Error> {
+        let quotation = self.pop_quotation("exec")?;
+        self.run(&quotation)
+    }
+
+    //runs the quotation with exec on what is on the stack
+    fn call(&mut self, quotation: &Rc<Chunk>) -> Result<(), Error> {
+        self.push(StackValue::Quotation(Rc::clone(quotation)));
+        self.exec()
+    }
+
+    //( x quot -- x ) runs the quotation without the value on top and puts it back afterwards
+    pub(crate) fn dip(&mut self) -> Result<(), Error> {
+        let quotation = self.pop_quotation("dip")?;
+        let value = self.pop_value()?;
+        self.call(&quotation)?;
+        self.push(value);
+        Ok(())
+    }
+
+    //( x quot -- ... x ) runs the quotation on the value and keeps a copy of the value on top
+    pub(crate) fn keep(&mut self) -> Result<(), Error> {
+        let quotation = self.pop_quotation("keep")?;
+        let value = self.pop_value()?;
+        self.push(value.clone());
+        self.call(&quotation)?;
+        self.push(value);
+        Ok(())
+    }
+
+    //runs each of the quotations on its own copy of the value
+    fn cleave_with(&mut self, value: StackValue, quotations: &[Rc<Chunk>]) -> Result<(), Error> {
+        for quotation in quotations {
+            self.push(value.clone());
+            self.call(quotation)?;
         }
-    }    
+        Ok(())
+    }
 
-    //tokenizes input by taking &str and converts it to a vector of &str
-    fn tokenize(input: &str) -> Vec<&str> {
-        //initializes a vector to store tokens, bool to track if we're inside quotes and index of current token's start pos
-        let mut tokens = Vec::new();
-        let mut in_quotes = false; 
-        let mut token_start = 0; 
-    
-        //iterates over the input string and its character indices
-        for (i, c) in input.char_indices() {
-            match c {
-                //if the character is a double quote, toggle the in_quotes flag
-                '"' => {
-                    in_quotes = !in_quotes;
-                    //if we're leaving the quoted section, add the token to the vector
-                    if !in_quotes {
-                        tokens.push(&input[token_start..i + 1]);
-                        token_start = i + 1;
-                    }
-                }
-                //if the character is a whitespace, and we're not inside quotes
-                ' ' | '\t' | '\n' | '\r' => {
-                    if !in_quotes {
-                        //if we have a non-empty token, add it to the vector
-                        if i > token_start {
-                            tokens.push(&input[token_start..i]);
-                        }
-                        token_start = i + 1;
-                    }
-                }
-                //ignore other characters
-                _ => {}
+    //( x p q -- ... ) runs p on x and then q on x
+    pub(crate) fn bi(&mut self) -> Result<(), Error> {
+        let second = self.pop_quotation("bi")?;
+        let first = self.pop_quotation("bi")?;
+        let value = self.pop_value()?;
+        self.cleave_with(value, &[first, second])
+    }
+
+    //( x p q r -- ... ) runs p, q and r on x
+    pub(crate) fn tri(&mut self) -> Result<(), Error> {
+        let third = self.pop_quotation("tri")?;
+        let second = self.pop_quotation("tri")?;
+        let first = self.pop_quotation("tri")?;
+        let value = self.pop_value()?;
+        self.cleave_with(value, &[first, second, third])
+    }
+
+    //( x quotations -- ... ) runs every quotation in the list on x
+    pub(crate) fn cleave(&mut self) -> Result<(), Error> {
+        let mut quotations = Vec::new();
+        for element in self.pop_list("cleave")? {
+            match element {
+                StackValue::Quotation(quotation) => quotations.push(quotation),
+                other => return Err(Error::type_mismatch("cleave", "a list of quotations", &[&other])),
             }
         }
-    
-        //if there's a token at the end of the input, add it to the vector
-        if token_start < input.len() {
-            tokens.push(&input[token_start..]);
-        }
-    
-        tokens 
+        let value = self.pop_value()?;
+        self.cleave_with(value, &quotations)
     }
-    
-    //function to parse the if blocks
-    fn parse_if_blocks(&mut self, tokens: &[String], start_index: usize) -> Result<(String, String, usize), &'static str> {
-        //variables to differentiate which block which is being evaluated 
-        let mut true_block = Vec::new();
-        let mut false_block = Vec::new();
-        let mut block_counter = 1;
-        let mut index = start_index;
-        let mut current_block = &mut true_block;
-    
-        while index < tokens.len() {
-            let token = &tokens[index];
-            match token.as_str() {
-                "if" => {
-                    current_block.push(token.clone());
-                    block_counter += 1;
-                }
-                //pushes that you are inside a block
-                "{" => {
-                    current_block.push(token.clone());
-                }
-                //-1 for block counter because it is now outside the block
-                "}" => {
-                    block_counter -= 1;
-                    if block_counter == 0 {
-                        index += 1;
-                        break;
-                    } else if block_counter == 1 {
-                        current_block = &mut false_block;
-                    } else {
-                        current_block.push(token.clone());
-                    }
-                }
-                //if not going out or coming in of block, push content to the block
-                _ => {
-                    current_block.push(token.clone());
+
+    //( x y quot -- ... ) runs the quotation on x and then on y
+    pub(crate) fn bi_at(&mut self) -> Result<(), Error> {
+        let quotation = self.pop_quotation("bi@")?;
+        let second = self.pop_value()?;
+        let first = self.pop_value()?;
+        self.push(first);
+        self.call(&quotation)?;
+        self.push(second);
+        self.call(&quotation)
+    }
+
+    pub(crate) fn process_map(&mut self) -> Result<(), Error> {
+        //checks if there is a quotation and list, because it needs it to evaluate it
+        let code_block = self.pop_quotation("map")?;
+        let list = match self.pop_value()? {
+            StackValue::Vec(list) => list,
+            //an infinite list remembers the quotation and applies it when values are taken from it
+            StackValue::Lazy(sequence) => {
+                self.push(StackValue::Lazy(sequence.with_step(Step::Map(code_block))));
+                return Ok(());
+            }
+            //the quotation gets the key and the value of each entry and leaves the new value
+            StackValue::Map(map) => {
+                let mut new_map = BTreeMap::new();
+                for (key, value) in map {
+                    self.push(key.to_value());
+                    new_map.insert(key, self.apply(&code_block, value)?);
                 }
+                self.push(StackValue::Map(new_map));
+                return Ok(());
             }
-            index += 1;
-        }
+            other => return Err(Error::type_mismatch("map", "a list or a map", &[&other])),
+        };
     
-        if block_counter != 0 {
-            return Err("Missing closing '}' for 'if' block");
+        let mut new_list = Vec::new();
+        //for each element in the list apply the quotation (code) on each element in the list
+        for value in list {
+            new_list.push(self.apply(&code_block, value)?);
         }
     
-        let true_block_str = true_block.join(" ");
-        let false_block_str = false_block.join(" ");
-        Ok((true_block_str, false_block_str, index))
-    } 
+        //push the new list
+        self.push(StackValue::Vec(List::from(new_list)));
+        Ok(())
+    }
 
-    fn process_map(&mut self) -> Result<(), &'static str> {
-        //checks if there is a quotation and list, because it needs it to evaluate it
-        let code_block = match self.pop() {
-            Some(StackValue::Quotation(block)) => block,
-            _ => return Err("Expected a code block for 'map'"),
-        };
-    
-        let list = match self.pop() {
-            Some(StackValue::Vec(l)) => l,
-            _ => return Err("Expected a list for 'map'"),
+    //keeps the elements the quotation leaves True for
+    pub(crate) fn process_filter(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("filter")?;
+        let list = match self.pop_value()? {
+            StackValue::Vec(list) => list,
+            StackValue::Lazy(sequence) => {
+                self.push(StackValue::Lazy(sequence.with_step(Step::Filter(code_block))));
+                return Ok(());
+            }
+            other => return Err(Error::type_mismatch("filter", "a list", &[&other])),
         };
-    
+
         let mut new_list = Vec::new();
-        //for each element in the least apply the quotation (code) on each element in the list
         for value in list {
-            self.push(value);
-            self.push(StackValue::Quotation(code_block.clone()));
-            self.exec()?;
-            new_list.push(self.pop().unwrap());
+            if self.test(&code_block, value.clone(), "filter")? {
+                new_list.push(value);
+            }
         }
-    
-        //push the new list
-        self.push(StackValue::Vec(new_list));
+        self.push(StackValue::Vec(List::from(new_list)));
         Ok(())
     }
      
-    pub fn process_each(&mut self) -> Result<(), &'static str> {
+    pub fn process_each(&mut self) -> Result<(), Error> {
         //checks for a quotation and a lsit if not returns error
-        let code_block = match self.pop() {
-            Some(StackValue::Quotation(block)) => block,
-            _ => return Err("Expected a code block for 'each'"),
-        };
-    
-        let list = match self.pop() {
-            Some(StackValue::Vec(l)) => l,
-            _ => return Err("Expected a list for 'each'"),
+        let code_block = self.pop_quotation("each")?;
+        let list = match self.pop_value()? {
+            StackValue::Vec(list) => list,
+            //there is no end, so instead of running forever it stops with an error after so many values unless the
+            //quotation fails first. Only one value of the list is made at a time, programs that want part of it use take first
+            StackValue::Lazy(mut sequence) => {
+                for _ in 0..MAX_LAZY_VALUES {
+                    let (value, rest) = self.next_of(&sequence)?;
+                    self.push(value);
+                    self.run(&code_block)?;
+                    sequence = rest;
+                }
+                return Err(Error::invalid("each went through a million values of an infinite list, use take to get the ones it needs"));
+            }
+            //the quotation gets the key and the value of each entry
+            StackValue::Map(map) => {
+                for (key, value) in map {
+                    self.push(key.to_value());
+                    self.push(value);
+                    self.run(&code_block)?;
+                }
+                return Ok(());
+            }
+            other => return Err(Error::type_mismatch("each", "a list or a map", &[&other])),
         };
 
         //does the quotation for each element but unlike map a new list isn't created
         for value in list {
             self.push(value);
-            self.push(StackValue::Quotation(code_block.clone()));
-            self.exec()?;
+            self.run(&code_block)?;
         }
         Ok(())
     }
 
-    fn process_foldl(&mut self) -> Result<(), &'static str> {
-        //checks for a quotation
-        let code_block = match self.pop() {
-            Some(StackValue::Quotation(block)) => block,
-            _ => return Err("Expected a code block for 'foldl'"),
+    //runs the quotation on the value and gives back what it left on the stack
+    fn apply(&mut self, code_block: &Rc<Chunk>, value: StackValue) -> Result<StackValue, Error> {
+        self.push(value);
+        self.run(code_block)?;
+        self.pop_value()
+    }
+
+    //runs the quotation on the value and gives back the bool it left on the stack
+    fn test(&mut self, code_block: &Rc<Chunk>, value: StackValue, op: &str) -> Result<bool, Error> {
+        self.push(value);
+        self.run(code_block)?;
+        self.pop_condition(op)
+    }
+
+    //the first value of an infinite list and the list of the values after it
+    fn next_of(&mut self, sequence: &Sequence) -> Result<(StackValue, Sequence), Error> {
+        let mut number = sequence.start;
+        'numbers: for _ in 0..MAX_LAZY_VALUES {
+            let mut value = StackValue::Int(number);
+            for step in &sequence.steps {
+                match step {
+                    Step::Map(block) => value = self.apply(block, value)?,
+                    Step::Filter(block) => {
+                        if !self.test(block, value.clone(), "filter")? {
+                            number = next_number(number)?;
+                            continue 'numbers;
+                        }
+                    }
+                }
+            }
+            let rest = Sequence { start: next_number(number)?, steps: sequence.steps.clone() };
+            return Ok((value, rest));
+        }
+        //a filter that never lets a value through would otherwise keep looking forever
+        Err(Error::invalid("filter found no value in a million values of an infinite list"))
+    }
+
+    //head and tail also work on infinite lists, which have to run their maps and filters to find the first value
+    pub(crate) fn head(&mut self) -> Result<(), Error> {
+        match self.pop_value()? {
+            StackValue::Lazy(sequence) => {
+                let (value, _) = self.next_of(&sequence)?;
+                self.push(value);
+                Ok(())
+            }
+            list => {
+                self.push(list.head()?);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn tail(&mut self) -> Result<(), Error> {
+        match self.pop_value()? {
+            StackValue::Lazy(sequence) => {
+                let (_, rest) = self.next_of(&sequence)?;
+                self.push(StackValue::Lazy(rest));
+                Ok(())
+            }
+            list => {
+                self.push(list.tail()?);
+                Ok(())
+            }
+        }
+    }
+
+    //the first n values of a list as a list, for infinite lists this is where the values are made
+    pub(crate) fn take(&mut self) -> Result<(), Error> {
+        let count = match self.pop_value()? {
+            StackValue::Int(count) if count >= 0 => count as usize,
+            StackValue::Int(_) => return Err(Error::invalid("Can't take a negative number of values")),
+            other => return Err(Error::type_mismatch("take", "an integer", &[&other])),
+        };
+        let taken = match self.pop_value()? {
+            //all of the list is the same list
+            StackValue::Vec(list) if count >= list.len() => {
+                self.push(StackValue::Vec(list));
+                return Ok(());
+            }
+            StackValue::Vec(list) => list.iter().take(count).cloned().collect(),
+            StackValue::Lazy(mut sequence) => {
+                let mut taken = Vec::new();
+                for _ in 0..count {
+                    let (value, rest) = self.next_of(&sequence)?;
+                    taken.push(value);
+                    sequence = rest;
+                }
+                taken
+            }
+            other => return Err(Error::type_mismatch("take", "a list", &[&other])),
         };
-        
-        let mut start = self.pop().unwrap();
+        self.push(StackValue::Vec(List::from(taken)));
+        Ok(())
+    }
+
+    //the list without its first n values, infinite lists make the values to skip them
+    pub(crate) fn drop(&mut self) -> Result<(), Error> {
+        let count = self.pop_count("drop")?;
+        match self.pop_value()? {
+            StackValue::Lazy(mut sequence) => {
+                for _ in 0..count {
+                    sequence = self.next_of(&sequence)?.1;
+                }
+                self.push(StackValue::Lazy(sequence));
+            }
+            list => self.push(list.drop(count)?),
+        }
+        Ok(())
+    }
 
+    pub(crate) fn process_foldl(&mut self) -> Result<(), Error> {
+        //checks for a quotation
+        let code_block = self.pop_quotation("foldl")?;
         //checks for start
-        let list = match self.pop() {
-            Some(StackValue::Vec(l)) => l,
-            _ => return Err("Expected a list for 'map'"),
-        };
+        let mut start = self.pop_value()?;
+        let list = self.pop_list("foldl")?;
 
         //for every value in the list
         //foldl works as we have a default start value which the user defines, we apply this to the first element
@@ -488,254 +644,352 @@ impl Stack {
         for value in list {
             self.push(start);
             self.push(value);
-            self.push(StackValue::Quotation(code_block.clone()));
-            self.exec()?;
-            start = self.pop().unwrap();
+            self.run(&code_block)?;
+            start = self.pop_value()?;
         }
 
         self.push(start);
         Ok(())
-    }    
-        
-    //processes an if sentence
-    fn process_if(&mut self, tokens: &[String], index: &mut usize) -> Result<(), &'static str> {
-        *index += 1;
-        //parses each if block
-        let (true_block, false_block, new_index) = self.parse_if_blocks(tokens, *index)?;
-        *index = new_index;
-    
+    }
+
+    //like foldl but from the right, the quotation gets the value and then the accumulator
+    pub(crate) fn process_foldr(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("foldr")?;
+        let mut accumulator = self.pop_value()?;
+        let list = self.pop_list("foldr")?;
+        for value in list.into_iter().collect::<Vec<StackValue>>().into_iter().rev() {
+            self.push(value);
+            self.push(accumulator);
+            self.run(&code_block)?;
+            accumulator = self.pop_value()?;
+        }
+        self.push(accumulator);
+        Ok(())
+    }
+
+    //sorts with a quotation that gets two values and leaves True if the first goes before the second
+    pub(crate) fn process_sort_by(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("sortBy")?;
+        let list = self.pop_list("sortBy")?;
+        let sorted = merge_sort(list.to_vec(), &mut |a, b| {
+            self.push(a.clone());
+            self.push(b.clone());
+            self.run(&code_block)?;
+            self.pop_condition("sortBy")
+        })?;
+        self.push(StackValue::Vec(List::from(sorted)));
+        Ok(())
+    }
+
+    //True if the quotation leaves True for any of the values, it stops at the first one
+    pub(crate) fn process_any(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("any")?;
+        let mut found = false;
+        for value in self.pop_list("any")? {
+            if self.test(&code_block, value, "any")? {
+                found = true;
+                break;
+            }
+        }
+        self.push(StackValue::Bool(found));
+        Ok(())
+    }
+
+    //True if the quotation leaves True for all of the values, it stops at the first one that fails
+    pub(crate) fn process_all(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("all")?;
+        let mut all = true;
+        for value in self.pop_list("all")? {
+            if !self.test(&code_block, value, "all")? {
+                all = false;
+                break;
+            }
+        }
+        self.push(StackValue::Bool(all));
+        Ok(())
+    }
+
+    //the first value the quotation leaves True for, it is an error if there is none
+    pub(crate) fn process_find(&mut self) -> Result<(), Error> {
+        let code_block = self.pop_quotation("find")?;
+        for value in self.pop_list("find")? {
+            if self.test(&code_block, value.clone(), "find")? {
+                self.push(value);
+                return Ok(());
+            }
+        }
+        Err(Error::invalid("None of the values in the list matched"))
+    }
+
+    //pops the quotation on top of the stack that the word op needs
+    pub(crate) fn pop_quotation(&mut self, op: &str) -> Result<Rc<Chunk>, Error> {
+        match self.pop_value()? {
+            StackValue::Quotation(block) => Ok(block),
+            other => Err(Error::type_mismatch(op, "a quotation", &[&other])),
+        }
+    }
+
+    //pops the list on top of the stack that the word op needs
+    pub(crate) fn pop_list(&mut self, op: &str) -> Result<List, Error> {
+        match self.pop_value()? {
+            StackValue::Vec(list) => Ok(list),
+            other => Err(Error::type_mismatch(op, "a list", &[&other])),
+        }
+    }
+
+    //pops the string on top of the stack that the word op needs
+    pub(crate) fn pop_string(&mut self, op: &str) -> Result<String, Error> {
+        match self.pop_value()? {
+            StackValue::String(string) => Ok(string),
+            other => Err(Error::type_mismatch(op, "a string", &[&other])),
+        }
+    }
+
+    //pops the int on top of the stack that the word op needs
+    pub(crate) fn pop_int(&mut self, op: &str) -> Result<i64, Error> {
+        match self.pop_value()? {
+            StackValue::Int(int) => Ok(int),
+            other => Err(Error::type_mismatch(op, "an integer", &[&other])),
+        }
+    }
+
+    //pops the int on top of the stack that the word op needs as a count, which can't be negative
+    pub(crate) fn pop_count(&mut self, op: &str) -> Result<usize, Error> {
+        let count = self.pop_int(op)?;
+        usize::try_from(count).map_err(|_| Error::invalid(&format!("'{}' can't take a negative number, got {}", op, count)))
+    }
+
+    //pops the bool a condition left on the stack
+    fn pop_condition(&mut self, op: &str) -> Result<bool, Error> {
+        match self.pop_value()? {
+            StackValue::Bool(value) => Ok(value),
+            other => Err(Error::type_mismatch(op, "a bool as the condition", &[&other])),
+        }
+    }
+
+    //if with the blocks on the stack, ( bool true_block false_block -- ) runs one of the blocks and keeps what it did
+    pub(crate) fn process_if(&mut self) -> Result<(), Error> {
+        let false_block = self.pop_quotation("if")?;
+        let true_block = self.pop_quotation("if")?;
+
         //the if chooses it's block based on the bool
-        let condition = match self.pop() {
-            Some(StackValue::Bool(value)) => value,
-            _ => return Err("Expected a boolean value for the condition"),
+        if self.pop_condition("if")? {
+            self.run(&true_block)
+        } else {
+            self.run(&false_block)
+        }
+    }
+
+    //the loops run their blocks from a rust loop, so the rust stack doesn't grow however long they run
+
+    //runs the body n times, ( n body -- )
+    pub(crate) fn process_times(&mut self) -> Result<(), Error> {
+        let body = self.pop_quotation("times")?;
+        let count = match self.pop_value()? {
+            StackValue::Int(count) => count,
+            other => return Err(Error::type_mismatch("times", "an integer", &[&other])),
         };
-    
-        let block_to_execute = if condition { &true_block } else { &false_block };
-        let prev_data = self.data.clone();
-        println!("Block to execute: {}", block_to_execute);
-        self.parse(block_to_execute)?;
-        self.data = prev_data;
-    
+
+        for _ in 0..count {
+            self.run(&body)?;
+        }
         Ok(())
     }
 
-    fn push_next(&mut self, temp_self: &mut Stack, tokens: Vec<&str>, index: &mut usize) {
-        *index += 1;
-        match temp_self.symbols.get(tokens[index.clone()]) {
-            Some(value) => temp_self.push(value.clone()),
-            //if it's not a symbol, try to parse it as a value and push it onto the stack
-            None => {
-                let value = temp_self.parse_value(tokens[index.clone()]).unwrap();
-                temp_self.push(value);
+    //runs the condition and stops when it is true, otherwise runs the body and starts over, ( condition body -- )
+    pub(crate) fn process_loop(&mut self) -> Result<(), Error> {
+        let body = self.pop_quotation("loop")?;
+        let condition = self.pop_quotation("loop")?;
+
+        loop {
+            self.run(&condition)?;
+            if self.pop_condition("loop")? {
+                return Ok(());
+            }
+            self.run(&body)?;
+        }
+    }
+
+    //runs the body as long as the condition is true, ( condition body -- )
+    pub(crate) fn process_while(&mut self) -> Result<(), Error> {
+        let body = self.pop_quotation("while")?;
+        let condition = self.pop_quotation("while")?;
+
+        loop {
+            self.run(&condition)?;
+            if !self.pop_condition("while")? {
+                return Ok(());
             }
+            self.run(&body)?;
         }
     }
 
     //parse a string and execute commands accordingly
-    pub fn parse(&mut self, input: &str) -> Result<(), &'static str> {
-    //based on input turns them into tokens
-    let tokens = Self::tokenize(input);
-    let mut temp_self = Stack::new();
-
-    //creates temporary stack so when an error occurs it doesnt do anything with the already parsed tokens
-    std::mem::swap(&mut self.data, &mut temp_self.data);
-
-    let mut index = 0;
-    //goes trough every token and matches them with function calls 
-    while index < tokens.len() {
-        let token = tokens[index];
-            match token {
-                "dup" => temp_self.dup()?,
-                "if" => {
-                    let tokens_as_string = tokens.iter().map(|s| s.to_string()).collect::<Vec<String>>();
-                    temp_self.process_if(&tokens_as_string, &mut index)?;
-                }
-                "swap" => temp_self.swap()?,
-                ":=" => temp_self.assignment()?,
-                "pop" => {
-                    temp_self.pop();
-                }            
-                "parseInteger" => temp_self.parse_integer()?,
-                "parseFloat" => temp_self.parse_float()?,
-                "words" => temp_self.words()?,
-                "print" => temp_self.print()?,
-                "read" => temp_self.read().map_err(|_| "Failed to read from stdin")?,
-                "exec" => temp_self.exec()?,
-                "map" => {
-                    self.push_next(&mut temp_self, tokens.clone(), &mut index);
-                    temp_self.process_map()?
-                },
-                "each" => {
-                    self.push_next(&mut temp_self, tokens.clone(), &mut index);
-                    temp_self.process_each()?
-                },
-                "foldl" => {
-                    self.push_next(&mut temp_self, tokens.clone(), &mut index);
-                    temp_self.process_foldl()?;
-                },
-                //needs a value after push to work
-                "push" => {
-                    if index + 1 < tokens.len() {
-                        let value = tokens[index + 1];
-                        let stack_value = temp_self.parse_value(value)?;
-                        temp_self.push(stack_value);
-                        index += 1;
-                    } else {
-                        return Err("Missing value after 'push'");
-                    }
-                }
-                //arithemtic operations ensures that the types are compatible
-                "+" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Int(x + y)),
-                    (StackValue::Float(x), StackValue::Float(y)) => Ok(StackValue::Float(x + y)),
-                    _ => Err("Mismatched types for addition"),
-                })?,
-                "-" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Int(x - y)),
-                    (StackValue::Float(x), StackValue::Float(y)) => Ok(StackValue::Float(x - y)),
-                    _ => Err("Mismatched types for subtraction"),
-                })?,
-                "*" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Int(x * y)),
-                    (StackValue::Float(x), StackValue::Float(y)) => Ok(StackValue::Float(x * y)),
-                    _ => Err("Mismatched types for multiplication"),
-                })?,
-                "/" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => {
-                        //doesn't allow division by zero because it isn't possible
-                        if y == 0 {
-                            Err("Division by zero")
-                        } else {
-                            Ok(StackValue::Float(x as f64 / y as f64))
-                        }
-                    }
-                    (StackValue::Float(x), StackValue::Float(y)) => {
-                        if y == 0.0 {
-                            Err("Division by zero")
-                        } else {
-                            Ok(StackValue::Float(x / y))
-                        }
-                    }
-                    _ => Err("Mismatched types for floating point division"),
-                })?,
-                "div" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => {
-                        if y == 0 {
-                            Err("Division by zero")
-                        } else {
-                            Ok(StackValue::Int(x / y))
-                        }
-                    }
-                    _ => Err("Mismatched types for integer division"),
-                })?,
-                "<" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Bool(x < y)),
-                    (StackValue::Float(x), StackValue::Float(y)) => Ok(StackValue::Bool(x < y)),
-                    _ => Err("Mismatched types for '<' comparison"),
-                })?,
-                ">" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Int(x), StackValue::Int(y)) => Ok(StackValue::Bool(x > y)),
-                    (StackValue::Float(x), StackValue::Float(y)) => Ok(StackValue::Bool(x > y)),
-                    _ => Err("Mismatched types for '>' comparison"),
-                })?,
-                "&&" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Bool(x), StackValue::Bool(y)) => Ok(StackValue::Bool(x && y)),
-                    _ => Err("Mismatched types for logical AND"),
-                })?,
-                "||" => temp_self.binary_op(|left, right| match (left, right) {
-                    (StackValue::Bool(x), StackValue::Bool(y)) => Ok(StackValue::Bool(x || y)),
-                    _ => Err("Mismatched types for logical OR"),
-                })?,
-                //needs two values to compare them
-                "==" => {
-                    let rhs = temp_self.pop().ok_or("No value on the stack")?;
-                    let lhs = temp_self.pop().ok_or("No value on the stack")?;
-                    let comparison_result = lhs == rhs;
-                    temp_self.push(StackValue::Bool(comparison_result));
+    pub fn parse(&mut self, input: &str) -> Result<(), Error> {
+        self.parse_source("<stdin>", input)
+    }
+
+    //same as parse but the spans of the tokens point into the given file
+    pub fn parse_source(&mut self, file: &str, input: &str) -> Result<(), Error> {
+        //based on input turns them into tokens, then into nodes and then into instructions
+        let tokens = tokenize(file, input)?;
+        let program = Rc::new(compile(&parse_program(&tokens)?)?);
+
+        //keeps a copy of the stack so when an error occurs it doesnt do anything with the already parsed tokens
+        let prev_data = self.data.clone();
+        let result = self.run(&program);
+        if result.is_err() {
+            self.data = prev_data;
+        }
+        result
+    }
+
+    //the virtual machine, runs the instructions of a chunk until it returns
+    pub fn run(&mut self, chunk: &Rc<Chunk>) -> Result<(), Error> {
+        //each nested run uses the rust stack, so they are stopped before it runs out
+        if self.nested_runs == MAX_NESTED_RUNS {
+            return Err(Error::new(ErrorKind::CallStackOverflow));
+        }
+        self.nested_runs += 1;
+        let result = self.run_chunk(chunk);
+        self.nested_runs -= 1;
+        result
+    }
+
+    fn run_chunk(&mut self, chunk: &Rc<Chunk>) -> Result<(), Error> {
+        let mut chunk = chunk.clone();
+        let mut pc = 0;
+        //where to continue when a user word or a quotation run by exec or if returns, kept here instead of on the rust stack
+        //so recursing through them can go deep
+        let mut frames: Vec<(Rc<Chunk>, usize)> = Vec::new();
+
+        self.run_instructions(&mut chunk, &mut pc, &mut frames).map_err(|mut error| {
+            //points the error at the instruction that failed, or if it came from code run by that instruction adds it to the trace
+            if let Some(span) = chunk.spans.get(pc) {
+                if error.span.is_none() {
+                    error = error.at(span);
+                } else {
+                    error.trace.push((chunk.called_word(pc).to_string(), span.clone()));
                 }
-                "not" => {
-                    if let Some(value) = temp_self.pop() {
-                        match value {
-                            //only works for int float and bool
-                            StackValue::Int(x) => temp_self.push(StackValue::Int(-x)),
-                            StackValue::Float(x) => temp_self.push(StackValue::Float(-x)),
-                            StackValue::Bool(x) => temp_self.push(StackValue::Bool(!x)),
-                            _ => return Err("Invalid type for negation or logical NOT"),
-                        }
-                    } else {
-                        return Err("Stack is empty");
-                    }
-                },
-                "head" => {
-                    if let Some(value) = temp_self.pop() {
-                        let head = value.head()?;
-                        temp_self.push(head);
-                    } else {
-                        return Err("Stack is empty");
-                    }
+            }
+            //then the calls to the user words that were running
+            for (caller, call_pc) in frames.iter().rev() {
+                error.trace.push((caller.called_word(*call_pc).to_string(), caller.spans[*call_pc].clone()));
+            }
+            error
+        })
+    }
+
+    //the arms are kept small and the work is done in other methods, so a nested run takes less of the rust stack
+    fn run_instructions(&mut self, chunk: &mut Rc<Chunk>, pc: &mut usize, frames: &mut Vec<(Rc<Chunk>, usize)>) -> Result<(), Error> {
+        loop {
+            let body = match chunk.code[*pc] {
+                Instr::Push(index) => {
+                    self.push(chunk.constants[index].clone());
+                    None
                 }
-                "tail" => {
-                    if let Some(value) = temp_self.pop() {
-                        let tail = value.tail()?;
-                        temp_self.push(tail);
-                    } else {
-                        return Err("Stack is empty");
-                    }
+                Instr::CallBuiltin(index) => {
+                    (BUILTINS[index].1)(self)?;
+                    None
                 }
-                "empty" => {
-                    if let Some(value) = temp_self.pop() {
-                        let is_empty = value.is_empty()?;
-                        temp_self.push(is_empty);
-                    } else {
-                        return Err("Stack is empty");
-                    }
+                Instr::CallWord(index) => self.call_word(&chunk.names[index])?,
+                Instr::Jump(target) => {
+                    *pc = target;
+                    continue;
                 }
-                "length" => {
-                    if let Some(value) = temp_self.pop() {
-                        let length = value.length()?;
-                        temp_self.push(length);
-                    } else {
-                        return Err("Stack is empty");
+                Instr::JumpIfFalse(target) => {
+                    if !self.pop_condition("if")? {
+                        *pc = target;
+                        continue;
                     }
+                    None
                 }
-                "cons" => temp_self.cons()?,
-                "append" => temp_self.append()?,
-                _ => {
-                    //possible assignment
-                    if token.contains(" ") {
-                        let parts: Vec<&str> = token.split(" ").collect();
-                        //checks the syntax for assignment, three parts and a := in the middle
-                        if parts.len() == 3 && parts[1] == ":=" {
-                            //sets the first to be symbol (variable) and second to be value, [1] is the :=
-                            let symbol = parts[0];
-                            let value_str = parts[2];
-        
-                            //assings the symbol to the value
-                            let value = temp_self.parse_value(value_str)?;
-                            temp_self.assign(symbol, value)?;
-                        } else {
-                            return Err("Invalid assignment syntax");
-                        }
-                    //if no space it can either be a symbol or a value
-                    } else { 
-                        //checks if the token is a symbol by looking it up in the symbols hashmap, and push the associated value onto the stack if it is
-                        match temp_self.symbols.get(token) {
-                            Some(value) => temp_self.push(value.clone()),
-                            //if it's not a symbol, try to parse it as a value and push it onto the stack
-                            None => {
-                                let value = temp_self.parse_value(token)?;
-                                temp_self.push(value);
-                            }
-                        }
+                Instr::Exec => Some(self.pop_quotation("exec")?),
+                Instr::If => Some(self.chosen_block()?),
+                Instr::Return => match frames.pop() {
+                    //continues after the call that was made from the caller
+                    Some((caller, call_pc)) => {
+                        *chunk = caller;
+                        *pc = call_pc;
+                        None
                     }
-                }
+                    None => return Ok(()),
+                },
+            };
+            //quotations are run by continuing in their code
+            match body {
+                Some(body) => enter(body, chunk, pc, frames)?,
+                None => *pc += 1,
             }
-            //next token
-            index += 1;
         }
-        //if no error has occured, we can safely push it to the actual stack 
-        std::mem::swap(&mut self.data, &mut temp_self.data);
+    }
+
+    //calls a registered word or a variable, giving back the quotation to run when the word is bound to one
+    fn call_word(&mut self, name: &str) -> Result<Option<Rc<Chunk>>, Error> {
+        //registered words are called the same way as built-ins
+        if let Some((arity, word)) = self.natives.get(name).cloned() {
+            self.call_native(name, arity, &word)?;
+            return Ok(None);
+        }
+        //checks if the word is a symbol by looking it up in the symbols hashmap
+        match self.symbols.get(name).cloned() {
+            Some(StackValue::Quotation(body)) => return Ok(Some(body)),
+            //other values are pushed onto the stack
+            Some(value) => self.push(value),
+            //if it's not a symbol, try to parse it as a value and push it onto the stack
+            None => {
+                let value = word_value(name)?;
+                self.push(value);
+            }
+        }
+        Ok(None)
+    }
+
+    //( bool true_block false_block -- ) the block the stack form of if runs
+    fn chosen_block(&mut self) -> Result<Rc<Chunk>, Error> {
+        let false_block = self.pop_quotation("if")?;
+        let true_block = self.pop_quotation("if")?;
+        Ok(if self.pop_condition("if")? { true_block } else { false_block })
+    }
+}
+
+//how many values of an infinite list each goes through, and how many a filter skips looking for the next one,
+//before stopping with an error instead of running forever
+const MAX_LAZY_VALUES: usize = 1_000_000;
+
+//how many calls to user words, exec and if can be nested in one run before it is treated as endless recursion
+const MAX_CALL_DEPTH: usize = 1_000_000;
+
+//how many runs can be inside each other, like map running a quotation that runs map. Each one uses up to 5 KB of the
+//rust stack in a debug build, so they fit in half of the 2 MB a new thread gets
+const MAX_NESTED_RUNS: usize = 200;
+
+//continues in the body of a quotation, remembering where to come back to unless the call is the last thing the chunk does
+fn enter(body: Rc<Chunk>, chunk: &mut Rc<Chunk>, pc: &mut usize, frames: &mut Vec<(Rc<Chunk>, usize)>) -> Result<(), Error> {
+    if !is_tail_call(chunk, *pc) {
+        if frames.len() >= MAX_CALL_DEPTH {
+            return Err(Error::new(ErrorKind::CallStackOverflow));
+        }
+        frames.push((chunk.clone(), *pc));
+    }
+    *chunk = body;
+    *pc = 0;
     Ok(())
-    }   
+}
 
-}
\ No newline at end of file
+//checks if nothing but jumps are left after the instruction at pc before the chunk returns
+fn is_tail_call(chunk: &Chunk, pc: usize) -> bool {
+    let mut next = pc + 1;
+    loop {
+        match chunk.code[next] {
+            Instr::Jump(target) => next = target,
+            Instr::Return => return true,
+            _ => return false,
+        }
+    }
+}
+
+//the number after the last one an infinite list used
+fn next_number(number: i64) -> Result<i64, Error> {
+    number.checked_add(1).ok_or(Error::invalid("The infinite list went past the largest integer"))
+}
@@ -1,79 +1,348 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
+
+use crate::bytecode::Chunk;
+use crate::error::*;
+use crate::list::List;
+use crate::numeric::{add_values, compare, multiply_values, values_equal, Rational};
 
 //has debug, clone and partialeq trait
 #[derive(Debug, Clone, PartialEq)]
 //different types the programming languages accepts
 pub enum StackValue {
-    Int(i32),
+    Int(i64),
     Float(f64),
+    //an exact fraction like 1/3r
+    Rational(Rational),
     Bool(bool),
     String(String),
-    Vec(Vec<StackValue>),
+    //shared with the copies of the list, so copying a list is cheap
+    Vec(List),
     Symbol(String),
-    Quotation(Vec<StackValue>),
+    //a block of compiled code that can be executed later
+    Quotation(Rc<Chunk>),
+    //an infinite list like [1..], its values are only made when they are needed
+    Lazy(Sequence),
+    //a dictionary like {| "a" 1 "b" 2 |}, kept in the order of its keys
+    Map(BTreeMap<Key, StackValue>),
+}
+
+//the values that can be keys of a map
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Key {
+    Bool(bool),
+    Int(i64),
+    String(String),
+    Symbol(String),
+}
+
+impl Key {
+    //None for values that can't be keys
+    pub fn from_value(value: &StackValue) -> Option<Key> {
+        match value {
+            StackValue::Bool(x) => Some(Key::Bool(*x)),
+            StackValue::Int(x) => Some(Key::Int(*x)),
+            StackValue::String(x) => Some(Key::String(x.clone())),
+            StackValue::Symbol(x) => Some(Key::Symbol(x.clone())),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> StackValue {
+        match self {
+            Key::Bool(x) => StackValue::Bool(*x),
+            Key::Int(x) => StackValue::Int(*x),
+            Key::String(x) => StackValue::String(x.clone()),
+            Key::Symbol(x) => StackValue::Symbol(x.clone()),
+        }
+    }
+}
+
+//strings and symbols in a map are shown the way they are written so the keys can be told apart
+fn write_literal(f: &mut Formatter, value: &StackValue) -> fmt::Result {
+    match value {
+        StackValue::String(x) => write!(f, "{:?}", x),
+        StackValue::Symbol(x) => write!(f, ":{}", x),
+        value => write!(f, "{}", value),
+    }
+}
+
+//the integers counting up from start, with the maps and filters applied to the list in the order they were applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sequence {
+    pub start: i64,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Map(Rc<Chunk>),
+    Filter(Rc<Chunk>),
+}
+
+impl Sequence {
+    pub fn from(start: i64) -> Self {
+        Sequence { start, steps: Vec::new() }
+    }
+
+    //the same list with one more map or filter applied to it
+    pub fn with_step(&self, step: Step) -> Self {
+        let mut steps = self.steps.clone();
+        steps.push(step);
+        Sequence { start: self.start, steps }
+    }
+}
+
+//the type of a value as far as the elements of a list have to match
+#[derive(Debug, Clone, PartialEq)]
+enum ValueType {
+    //the name of the variant for everything that isn't a list
+    Plain(&'static str),
+    //a list with the type of its elements, None when it is empty and could hold anything
+    List(Option<Box<ValueType>>),
+    //a tuple that mixes types, with the type at each place
+    Tuple(Vec<ValueType>),
+}
+
+impl ValueType {
+    fn of(value: &StackValue) -> ValueType {
+        match value {
+            StackValue::Vec(list) => match ValueType::common(list.iter()) {
+                Some(element) => ValueType::List(element.map(Box::new)),
+                None => ValueType::Tuple(list.iter().map(ValueType::of).collect()),
+            },
+            StackValue::Int(_) => ValueType::Plain("int"),
+            StackValue::Float(_) => ValueType::Plain("float"),
+            StackValue::Rational(_) => ValueType::Plain("rational"),
+            StackValue::Bool(_) => ValueType::Plain("bool"),
+            StackValue::String(_) => ValueType::Plain("string"),
+            StackValue::Symbol(_) => ValueType::Plain("symbol"),
+            StackValue::Quotation(_) => ValueType::Plain("quotation"),
+            StackValue::Lazy(_) => ValueType::Plain("lazy"),
+            StackValue::Map(_) => ValueType::Plain("map"),
+        }
+    }
+
+    //the one type all the values have, Some(None) when there are no values and None when they don't share a type
+    fn common<'a>(values: impl Iterator<Item = &'a StackValue>) -> Option<Option<ValueType>> {
+        let mut common: Option<ValueType> = None;
+        for value in values {
+            let value_type = ValueType::of(value);
+            common = Some(match common {
+                Some(common) => common.unify(value_type)?,
+                None => value_type,
+            });
+        }
+        Some(common)
+    }
+
+    //the type that covers both, an empty list fits any list or tuple
+    fn unify(self, other: ValueType) -> Option<ValueType> {
+        match (self, other) {
+            (ValueType::List(None), list @ (ValueType::List(_) | ValueType::Tuple(_))) => Some(list),
+            (list @ (ValueType::List(_) | ValueType::Tuple(_)), ValueType::List(None)) => Some(list),
+            (ValueType::List(Some(a)), ValueType::List(Some(b))) => Some(ValueType::List(Some(Box::new(a.unify(*b)?)))),
+            (ValueType::Tuple(a), ValueType::Tuple(b)) if a.len() == b.len() => {
+                Some(ValueType::Tuple(a.into_iter().zip(b).map(|(x, y)| x.unify(y)).collect::<Option<_>>()?))
+            }
+            (a, b) if a == b => Some(a),
+            _ => None,
+        }
+    }
 }
 
 //implemitation of the StackValue enum
 impl StackValue {
-    //matches inputted type
+    //matches inputted type, two lists are the same type if all their elements are of one type,
+    //e.g. [1] and [2 3] but not [1] and ["a"], or if they are tuples with the same types in the same places
     pub fn same_type(&self, other: &Self) -> bool {
-        match (self, other) {
-            (StackValue::Int(_), StackValue::Int(_))
-            | (StackValue::Float(_), StackValue::Float(_))
-            | (StackValue::Bool(_), StackValue::Bool(_))
-            | (StackValue::String(_), StackValue::String(_))
-            | (StackValue::Vec(_), StackValue::Vec(_))
-            | (StackValue::Symbol(_), StackValue::Symbol(_)) => true,
-            | (StackValue::Quotation(_), StackValue::Quotation(_)) => true,
-            _ => false,
-        }
+        ValueType::of(self).unify(ValueType::of(other)).is_some()
+    }
+
+    //checks that the values all have one type, which is what the elements of a [ ] list need
+    pub fn all_same_type(values: &[StackValue]) -> bool {
+        ValueType::common(values.iter()).is_some()
     }
 
     //method to reatrieve head of list
-    pub fn head(&self) -> Result<StackValue, &'static str> {
+    pub fn head(&self) -> Result<StackValue, Error> {
         match self {
             //uses vec.first() 
             StackValue::Vec(vec) => {
                 if let Some(first) = vec.first() {
                     Ok(first.clone())
                 } else {
-                    Err("The list is empty")
+                    Err(Error::invalid("The list is empty"))
                 }
             }
-            _ => Err("The value is not a list"),
+            _ => Err(Error::type_mismatch("head", "a list", &[self])),
         }
     }
 
     //method to get tail of list
-    pub fn tail(&self) -> Result<StackValue, &'static str> {
+    pub fn tail(&self) -> Result<StackValue, Error> {
         match self {
-            StackValue::Vec(vec) => {
-                if !vec.is_empty() {
-                    //everything but the first element 0
-                    Ok(StackValue::Vec(vec[1..].to_vec()))
-                } else {
-                    Err("The list is empty")
-                }
-            }
-            _ => Err("The value is not a list"),
+            //everything but the first element, sharing the values with the list
+            StackValue::Vec(vec) => match vec.tail() {
+                Some(tail) => Ok(StackValue::Vec(tail)),
+                None => Err(Error::invalid("The list is empty")),
+            },
+            _ => Err(Error::type_mismatch("tail", "a list", &[self])),
         }
     }
 
     //checks if a list is empty or not
-    pub fn is_empty(&self) -> Result<StackValue, &'static str> {
+    pub fn is_empty(&self) -> Result<StackValue, Error> {
         match self {
             StackValue::Vec(vec) => Ok(StackValue::Bool(vec.is_empty())),
-            _ => Err("The value is not a list"),
+            //an infinite list never runs out
+            StackValue::Lazy(_) => Ok(StackValue::Bool(false)),
+            _ => Err(Error::type_mismatch("empty", "a list", &[self])),
         }
     }
 
     //length of inputted values
-    pub fn length(&self) -> Result<StackValue, &'static str> {
+    pub fn length(&self) -> Result<StackValue, Error> {
+        match self {
+            StackValue::Vec(vec) => Ok(StackValue::Int(vec.len() as i64)),
+            StackValue::Lazy(_) => Err(Error::invalid("An infinite list has no length")),
+            _ => Err(Error::type_mismatch("length", "a list", &[self])),
+        }
+    }
+
+    //the elements of a list for the word op, anything else is a type mismatch
+    fn elements(&self, op: &str) -> Result<&List, Error> {
         match self {
-            StackValue::Vec(vec) => Ok(StackValue::Int(vec.len() as i32)),
-            _ => Err("The value is not a list"),
+            StackValue::Vec(vec) => Ok(vec),
+            _ => Err(Error::type_mismatch(op, "a list", &[self])),
+        }
+    }
+
+    pub fn reverse(&self) -> Result<StackValue, Error> {
+        //consing each value onto the ones before it reverses the list
+        let list = self.elements("reverse")?;
+        Ok(StackValue::Vec(list.iter().cloned().fold(List::new(), List::cons)))
+    }
+
+    //the element at index, counted from 0
+    pub fn nth(&self, index: i64) -> Result<StackValue, Error> {
+        let vec = self.elements("nth")?;
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| vec.get(index))
+            .cloned()
+            .ok_or(Error::invalid(&format!("The index {} is out of range for a list of length {}", index, vec.len())))
+    }
+
+    //everything but the first count elements, sharing the rest of the list
+    pub fn drop(&self, count: usize) -> Result<StackValue, Error> {
+        Ok(StackValue::Vec(self.elements("drop")?.skip(count)))
+    }
+
+    //the elements from start up to but not including end, an end past the list is the end of the list
+    pub fn slice(&self, start: usize, end: usize) -> Result<StackValue, Error> {
+        let vec = self.elements("slice")?;
+        Ok(StackValue::Vec(vec.iter().take(end).skip(start).cloned().collect()))
+    }
+
+    //a list of lists joined into one list, only one level is flattened
+    pub fn flatten(&self) -> Result<StackValue, Error> {
+        let mut flat: Vec<StackValue> = Vec::new();
+        for element in self.elements("flatten")? {
+            match element {
+                StackValue::Vec(inner) => flat.extend(inner.iter().cloned()),
+                _ => return Err(Error::type_mismatch("flatten", "a list of lists", &[element])),
+            }
+        }
+        Ok(StackValue::Vec(List::from(flat)))
+    }
+
+    //pairs up the elements of two lists, stopping at the end of the shorter one
+    pub fn zip(&self, other: &StackValue) -> Result<StackValue, Error> {
+        let pairs = self.elements("zip")?.iter().zip(other.elements("zip")?);
+        Ok(StackValue::Vec(pairs.map(|(a, b)| StackValue::Vec(List::from(vec![a.clone(), b.clone()]))).collect()))
+    }
+
+    //splits a list of pairs into the list of the first values and the list of the second values
+    pub fn unzip(&self) -> Result<(StackValue, StackValue), Error> {
+        let (mut firsts, mut seconds) = (Vec::new(), Vec::new());
+        for pair in self.elements("unzip")? {
+            match pair {
+                StackValue::Vec(values) if values.len() == 2 => {
+                    let mut values = values.iter().cloned();
+                    firsts.extend(values.next());
+                    seconds.extend(values.next());
+                }
+                _ => return Err(Error::type_mismatch("unzip", "a list of pairs", &[pair])),
+            }
+        }
+        Ok((StackValue::Vec(List::from(firsts)), StackValue::Vec(List::from(seconds))))
+    }
+
+    //the sum of a list of numbers, 0 for an empty list
+    pub fn sum(&self) -> Result<StackValue, Error> {
+        self.elements("sum")?.iter().try_fold(StackValue::Int(0), |total, value| add_values(&total, value))
+    }
+
+    //the product of a list of numbers, 1 for an empty list
+    pub fn product(&self) -> Result<StackValue, Error> {
+        self.elements("product")?.iter().try_fold(StackValue::Int(1), |total, value| multiply_values(&total, value))
+    }
+
+    //the list with only the first of any values that are ==
+    pub fn uniq(&self) -> Result<StackValue, Error> {
+        let mut unique: Vec<StackValue> = Vec::new();
+        for value in self.elements("uniq")? {
+            if !unique.iter().any(|kept| values_equal(kept, value)) {
+                unique.push(value.clone());
+            }
         }
+        Ok(StackValue::Vec(List::from(unique)))
+    }
+
+    //the index of the first element that is == to the value, -1 when it isn't in the list
+    pub fn index_of(&self, value: &StackValue) -> Result<StackValue, Error> {
+        let index = self.elements("indexOf")?.iter().position(|element| values_equal(element, value));
+        Ok(StackValue::Int(index.map_or(-1, |index| index as i64)))
+    }
+
+    //sorts numbers, strings, bools or symbols from smallest to largest
+    pub fn sort(&self) -> Result<StackValue, Error> {
+        let sorted = merge_sort(self.elements("sort")?.to_vec(), &mut |a, b| match (a, b) {
+            (StackValue::String(x), StackValue::String(y)) => Ok(x < y),
+            (StackValue::Symbol(x), StackValue::Symbol(y)) => Ok(x < y),
+            (StackValue::Bool(x), StackValue::Bool(y)) => Ok(x < y),
+            _ => match compare(a, b) {
+                Some(order) => Ok(order == Ordering::Less),
+                None => Err(Error::type_mismatch("sort", "values that can be ordered", &[a, b])),
+            },
+        })?;
+        Ok(StackValue::Vec(List::from(sorted)))
+    }
+}
+
+//a stable sort where working out the order can fail, less tells if the first value goes before the second
+pub fn merge_sort<F>(mut values: Vec<StackValue>, less: &mut F) -> Result<Vec<StackValue>, Error>
+where
+    F: FnMut(&StackValue, &StackValue) -> Result<bool, Error>,
+{
+    if values.len() <= 1 {
+        return Ok(values);
+    }
+    let right = values.split_off(values.len() / 2);
+    let mut left = merge_sort(values, less)?.into_iter().peekable();
+    let mut right = merge_sort(right, less)?.into_iter().peekable();
+    let mut merged = Vec::new();
+    while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+        //the left one goes first unless the right one is smaller, which keeps equal values in order
+        let next = if less(b, a)? { right.next() } else { left.next() };
+        merged.extend(next);
     }
+    merged.extend(left);
+    merged.extend(right);
+    Ok(merged)
 }
 
 //determines how the StackValue should be displayed
@@ -82,6 +351,7 @@ impl Display for StackValue {
         match self {
             StackValue::Int(value) => write!(f, "{}", value),
             StackValue::Float(value) => write!(f, "{}", value),
+            StackValue::Rational(value) => write!(f, "{}", value),
             StackValue::Bool(value) => write!(f, "{}", value),
             StackValue::String(value) => write!(f, "{}", value),
             StackValue::Vec(value) => write!(f, "{:?}", value),
@@ -89,12 +359,34 @@ impl Display for StackValue {
             //has to deal with different types
             StackValue::Quotation(quot) => {
                 let quot_str = quot
+                    .source
                     .iter()
-                    .map(|stack_value| format!("{}", stack_value))
+                    .map(|node| format!("{}", node))
                     .collect::<Vec<String>>()
                     .join(" ");
                 write!(f, "{{{}}}", quot_str)
             },
+            StackValue::Map(map) => {
+                write!(f, "{{|")?;
+                for (key, value) in map {
+                    write!(f, " ")?;
+                    write_literal(f, &key.to_value())?;
+                    write!(f, " ")?;
+                    write_literal(f, value)?;
+                }
+                write!(f, " |}}")
+            }
+            //shown as the range followed by what was applied to it, e.g. [1..] map {dup *}
+            StackValue::Lazy(sequence) => {
+                write!(f, "[{}..]", sequence.start)?;
+                for step in &sequence.steps {
+                    match step {
+                        Step::Map(block) => write!(f, " map {}", StackValue::Quotation(block.clone()))?,
+                        Step::Filter(block) => write!(f, " filter {}", StackValue::Quotation(block.clone()))?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,269 @@
+use crate::error::*;
+use crate::numeric::to_f64;
+use crate::stack::*;
+use crate::stackvalues::*;
+
+//the string words, positions and lengths count characters and not bytes
+
+fn push_string(stack: &mut Stack, string: String) -> Result<(), Error> {
+    stack.push(StackValue::String(string));
+    Ok(())
+}
+
+fn push_bool(stack: &mut Stack, value: bool) -> Result<(), Error> {
+    stack.push(StackValue::Bool(value));
+    Ok(())
+}
+
+//( s1 s2 -- s1s2 )
+pub fn concat(stack: &mut Stack) -> Result<(), Error> {
+    let right = stack.pop_string("concat")?;
+    let left = stack.pop_string("concat")?;
+    push_string(stack, left + &right)
+}
+
+//( s separator -- list )
+pub fn split(stack: &mut Stack) -> Result<(), Error> {
+    let separator = stack.pop_string("split")?;
+    let string = stack.pop_string("split")?;
+    if separator.is_empty() {
+        return Err(Error::invalid("The separator of 'split' can't be empty, use chars instead"));
+    }
+    let parts = string.split(&separator).map(|part| StackValue::String(part.to_string())).collect();
+    stack.push(StackValue::Vec(parts));
+    Ok(())
+}
+
+//( list separator -- s ), the list has to be of strings
+pub fn join(stack: &mut Stack) -> Result<(), Error> {
+    let separator = stack.pop_string("join")?;
+    let list = stack.pop_list("join")?;
+    let parts = list
+        .iter()
+        .map(|value| match value {
+            StackValue::String(part) => Ok(part.as_str()),
+            other => Err(Error::type_mismatch("join", "a list of strings", &[other])),
+        })
+        .collect::<Result<Vec<&str>, Error>>()?;
+    push_string(stack, parts.join(&separator))
+}
+
+//( s start length -- s ), the length is cut short at the end of the string
+pub fn substr(stack: &mut Stack) -> Result<(), Error> {
+    let length = stack.pop_count("substr")?;
+    let start = stack.pop_count("substr")?;
+    let string = stack.pop_string("substr")?;
+    let count = string.chars().count();
+    if start > count {
+        return Err(Error::invalid(&format!("The start {} is past the end of the string of length {}", start, count)));
+    }
+    push_string(stack, string.chars().skip(start).take(length).collect())
+}
+
+//( s part -- index ) or ( list value -- index ), -1 when the part isn't in the string or the value in the list
+pub fn index_of(stack: &mut Stack) -> Result<(), Error> {
+    let needle = stack.pop_value()?;
+    let index = match (stack.pop_value()?, needle) {
+        (StackValue::String(string), StackValue::String(part)) => match string.find(&part) {
+            Some(byte) => StackValue::Int(string[..byte].chars().count() as i64),
+            None => StackValue::Int(-1),
+        },
+        (list @ StackValue::Vec(_), value) => list.index_of(&value)?,
+        (haystack, needle) => return Err(Error::type_mismatch("indexOf", "two strings or a list and a value", &[&haystack, &needle])),
+    };
+    stack.push(index);
+    Ok(())
+}
+
+//( s part -- bool ) or ( list value -- bool )
+pub fn contains(stack: &mut Stack) -> Result<(), Error> {
+    let needle = stack.pop_value()?;
+    match (stack.pop_value()?, needle) {
+        (StackValue::String(string), StackValue::String(part)) => push_bool(stack, string.contains(&part)),
+        (StackValue::Vec(list), value) => push_bool(stack, list.contains(&value)),
+        (haystack, needle) => Err(Error::type_mismatch("contains", "two strings or a list and a value", &[&haystack, &needle])),
+    }
+}
+
+//( s prefix -- bool )
+pub fn starts_with(stack: &mut Stack) -> Result<(), Error> {
+    let prefix = stack.pop_string("startsWith")?;
+    let string = stack.pop_string("startsWith")?;
+    push_bool(stack, string.starts_with(&prefix))
+}
+
+//( s suffix -- bool )
+pub fn ends_with(stack: &mut Stack) -> Result<(), Error> {
+    let suffix = stack.pop_string("endsWith")?;
+    let string = stack.pop_string("endsWith")?;
+    push_bool(stack, string.ends_with(&suffix))
+}
+
+pub fn to_upper(stack: &mut Stack) -> Result<(), Error> {
+    let string = stack.pop_string("toUpper")?;
+    push_string(stack, string.to_uppercase())
+}
+
+pub fn to_lower(stack: &mut Stack) -> Result<(), Error> {
+    let string = stack.pop_string("toLower")?;
+    push_string(stack, string.to_lowercase())
+}
+
+pub fn trim(stack: &mut Stack) -> Result<(), Error> {
+    let string = stack.pop_string("trim")?;
+    push_string(stack, string.trim().to_string())
+}
+
+//( s from to -- s ), replaces every occurrence
+pub fn replace(stack: &mut Stack) -> Result<(), Error> {
+    let to = stack.pop_string("replace")?;
+    let from = stack.pop_string("replace")?;
+    let string = stack.pop_string("replace")?;
+    if from.is_empty() {
+        return Err(Error::invalid("The text 'replace' looks for can't be empty"));
+    }
+    push_string(stack, string.replace(&from, &to))
+}
+
+//( s -- list ), every character as its own string
+pub fn chars(stack: &mut Stack) -> Result<(), Error> {
+    let string = stack.pop_string("chars")?;
+    let chars = string.chars().map(|c| StackValue::String(c.to_string())).collect();
+    stack.push(StackValue::Vec(chars));
+    Ok(())
+}
+
+//( s -- int ), the number of characters
+pub fn strlen(stack: &mut Stack) -> Result<(), Error> {
+    let string = stack.pop_string("strlen")?;
+    stack.push(StackValue::Int(string.chars().count() as i64));
+    Ok(())
+}
+
+//( s n -- s ), the string n times after each other
+pub fn repeat(stack: &mut Stack) -> Result<(), Error> {
+    let count = stack.pop_count("repeat")?;
+    let string = stack.pop_string("repeat")?;
+    if string.len().checked_mul(count).is_none() {
+        return Err(Error::invalid("The repeated string would be too long"));
+    }
+    push_string(stack, string.repeat(count))
+}
+
+//a {} in a format template, written as {:[<^>][0][width][.precision]}
+#[derive(Default)]
+struct Placeholder {
+    align: Option<char>,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+enum Piece {
+    Text(String),
+    Value(Placeholder),
+}
+
+fn template_error(template: &str, problem: &str) -> Error {
+    Error::invalid(&format!("Invalid format template \"{}\": {}", template, problem))
+}
+
+//splits the template into the text and the placeholders, {{ and }} are literal braces
+fn parse_template(template: &str) -> Result<Vec<Piece>, Error> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => return Err(template_error(template, "a { without a }")),
+                    }
+                }
+                pieces.push(Piece::Text(std::mem::take(&mut text)));
+                pieces.push(Piece::Value(parse_placeholder(&spec).ok_or(template_error(template, &format!("can't read {{{}}}", spec)))?));
+            }
+            '}' => return Err(template_error(template, "a } without a {")),
+            c => text.push(c),
+        }
+    }
+    pieces.push(Piece::Text(text));
+    Ok(pieces)
+}
+
+//what is between the braces of a placeholder, None if it can't be read
+fn parse_placeholder(spec: &str) -> Option<Placeholder> {
+    let mut placeholder = Placeholder::default();
+    if spec.is_empty() {
+        return Some(placeholder);
+    }
+    let mut spec = spec.strip_prefix(':')?;
+    if let Some(align) = spec.chars().next().filter(|c| matches!(c, '<' | '^' | '>')) {
+        placeholder.align = Some(align);
+        spec = &spec[1..];
+    }
+    if spec.starts_with('0') {
+        placeholder.zero = true;
+        spec = &spec[1..];
+    }
+    let (width, precision) = match spec.split_once('.') {
+        Some((width, precision)) => (width, Some(precision.parse().ok()?)),
+        None => (spec, None),
+    };
+    placeholder.width = if width.is_empty() { 0 } else { width.parse().ok()? };
+    placeholder.precision = precision;
+    Some(placeholder)
+}
+
+//shows the value the way print does, numbers can be given a precision and are aligned right by default
+fn render(value: &StackValue, placeholder: &Placeholder) -> String {
+    let number = to_f64(value);
+    let text = match (placeholder.precision, number) {
+        (Some(precision), Some(x)) => format!("{:.*}", precision, x),
+        _ => value.to_string(),
+    };
+    let padding = placeholder.width.saturating_sub(text.chars().count());
+    if placeholder.zero && number.is_some() {
+        //the zeros go after the sign
+        let (sign, digits) = if let Some(digits) = text.strip_prefix('-') { ("-", digits) } else { ("", text.as_str()) };
+        return format!("{}{}{}", sign, "0".repeat(padding), digits);
+    }
+    let default = if number.is_some() { '>' } else { '<' };
+    match placeholder.align.unwrap_or(default) {
+        '>' => format!("{}{}", " ".repeat(padding), text),
+        '^' => format!("{}{}{}", " ".repeat(padding / 2), text, " ".repeat(padding - padding / 2)),
+        _ => format!("{}{}", text, " ".repeat(padding)),
+    }
+}
+
+//( values template -- s ), each {} is filled with one of the values below the template in the order they were pushed
+pub fn format(stack: &mut Stack) -> Result<(), Error> {
+    let template = stack.pop_string("format")?;
+    let pieces = parse_template(&template)?;
+    let count = pieces.iter().filter(|piece| matches!(piece, Piece::Value(_))).count();
+    if stack.data.len() < count {
+        return Err(Error::new(ErrorKind::StackUnderflow));
+    }
+    let values = stack.data.split_off(stack.data.len() - count);
+    let mut values = values.iter();
+    let mut result = String::new();
+    for piece in &pieces {
+        match piece {
+            Piece::Text(text) => result.push_str(text),
+            Piece::Value(placeholder) => result.push_str(&render(values.next().unwrap(), placeholder)),
+        }
+    }
+    push_string(stack, result)
+}
@@ -1,5 +1,11 @@
 use crate::stack::*;
 use crate::stackvalues::*;
+use crate::lexer::*;
+use crate::list::*;
+use crate::parser::*;
+use crate::bytecode::*;
+use crate::builtins::*;
+use crate::error::*;
 
 //testing-----------
 #[cfg(test)]
@@ -7,22 +13,63 @@ mod tests {
     use super::*;
 
     //helper function to execute code and get the top value of the stack
-    fn execute_and_get_top(stack: &mut Stack, code: &str) -> Result<StackValue, &'static str> {
-        stack.parse(code)?;
-        stack.pop().ok_or("Empty stack")
+    fn execute_and_get_top(stack: &mut Stack, code: &str) -> Result<StackValue, ErrorKind> {
+        stack.parse(code).map_err(|e| e.kind)?;
+        stack.pop().ok_or(ErrorKind::StackUnderflow)
     }
 
     //helper function to execute code and get the top two values of the stack
-    fn execute_and_get_top_two(stack: &mut Stack, code: &str) -> Result<(StackValue, StackValue), &'static str> {
-        stack.parse(code)?;
+    fn execute_and_get_top_two(stack: &mut Stack, code: &str) -> Result<(StackValue, StackValue), ErrorKind> {
+        stack.parse(code).map_err(|e| e.kind)?;
         
-        let top = stack.pop().ok_or("Empty stack")?;
-        let second_top = stack.pop().ok_or("Only one element in the stack")?;
+        let top = stack.pop().ok_or(ErrorKind::StackUnderflow)?;
+        let second_top = stack.pop().ok_or(ErrorKind::StackUnderflow)?;
         stack.push(second_top.clone());
         stack.push(top.clone());
         Ok((top, second_top))
     }
 
+    //the error a word gives when it gets a value of the wrong type
+    fn mismatch(op: &str, expected: &'static str, found: &str) -> ErrorKind {
+        ErrorKind::TypeMismatch { op: op.to_string(), expected, found: found.to_string() }
+    }
+
+    //helper function to execute code and get the top value of the stack the way print shows it
+    fn execute_and_show(stack: &mut Stack, code: &str) -> Result<String, ErrorKind> {
+        execute_and_get_top(stack, code).map(|value| value.to_string())
+    }
+
+    fn int_values(values: &[i64]) -> Vec<StackValue> {
+        values.iter().map(|&x| StackValue::Int(x)).collect()
+    }
+
+    fn int_list(values: &[i64]) -> List {
+        List::from(int_values(values))
+    }
+
+    //a list value of ints
+    fn ints(values: &[i64]) -> StackValue {
+        StackValue::Vec(int_list(values))
+    }
+
+    fn string(value: &str) -> StackValue {
+        StackValue::String(value.to_string())
+    }
+
+    //a list value of strings
+    fn strings(values: &[&str]) -> StackValue {
+        StackValue::Vec(values.iter().map(|&value| string(value)).collect())
+    }
+
+    fn rational(numerator: i64, denominator: i64) -> StackValue {
+        StackValue::Rational(crate::numeric::Rational::new(numerator, denominator).unwrap())
+    }
+
+    //checks that the list after the first value of a is the list b itself
+    fn first_shares(a: &List, b: &List) -> bool {
+        a.tail().is_some_and(|tail| tail.ptr_eq(b))
+    }
+
     //testing that the literals work
     #[test]
     fn literals() {
@@ -77,21 +124,21 @@ mod tests {
     
         assert_eq!(
             execute_and_get_top(&mut stack, "1 [2,3] cons"),
-            Ok(StackValue::Vec(vec![
+            Ok(StackValue::Vec(List::from(vec![
                 StackValue::Int(1),
                 StackValue::Int(2),
                 StackValue::Int(3)
-            ]))
+            ])))
         );
     
         assert_eq!(
             execute_and_get_top(&mut stack, "[3,4] [1,2] append"),
-            Ok(StackValue::Vec(vec![
+            Ok(StackValue::Vec(List::from(vec![
                 StackValue::Int(1),
                 StackValue::Int(2),
                 StackValue::Int(3),
                 StackValue::Int(4)
-            ]))
+            ])))
         );
     
         assert_eq!(
@@ -101,7 +148,7 @@ mod tests {
     
         assert_eq!(
             execute_and_get_top(&mut stack, "[1,2,3] tail"),
-            Ok(StackValue::Vec(vec![StackValue::Int(2), StackValue::Int(3)]))
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(2), StackValue::Int(3)])))
         );
     
         assert_eq!(
@@ -117,6 +164,7 @@ mod tests {
 
     //tests stack operations
     #[test]
+    #[allow(clippy::approx_constant)]
     fn stack_operations() {
         let mut stack = Stack::new();
     
@@ -158,11 +206,11 @@ mod tests {
         stack.push(StackValue::String("one two three".to_string()));
         assert_eq!(
             execute_and_get_top(&mut stack, "words"),
-            Ok(StackValue::Vec(vec![
+            Ok(StackValue::Vec(List::from(vec![
                 StackValue::String("one".to_string()),
                 StackValue::String("two".to_string()),
                 StackValue::String("three".to_string()),
-            ]))
+            ])))
         );
     }
 
@@ -173,11 +221,11 @@ mod tests {
         // Test case: Map with a list of integers and 10 times multiplication
         assert_eq!(
             execute_and_get_top(&mut stack, "10 10 10 [1,2,3] map {*}"),
-            Ok(StackValue::Vec(vec![
+            Ok(StackValue::Vec(List::from(vec![
                 StackValue::Int(10),
                 StackValue::Int(20),
                 StackValue::Int(30)
-            ]))
+            ])))
         );
     }
 
@@ -187,14 +235,12 @@ mod tests {
     
         // Test case: Each with a list of integers and 10 times multiplication
         stack.push(StackValue::Int(10));
-        stack.push(StackValue::Vec(vec![
+        stack.push(StackValue::Vec(List::from(vec![
             StackValue::Int(1),
             StackValue::Int(2),
             StackValue::Int(3),
-        ]));
-        stack.push(StackValue::Quotation(vec![
-            StackValue::Symbol(String::from("*"))
-        ]));
+        ])));
+        stack.parse("{*}").unwrap();
         stack.process_each().unwrap();
         assert_eq!(stack.pop(), Some(StackValue::Int(60)));
     }
@@ -210,4 +256,755 @@ mod tests {
         );
     }
 
-}
\ No newline at end of file
+    //tests that the lexer gives typed tokens with the line and column they were found at
+    #[test]
+    fn lexer_tokens_and_spans() {
+        let tokens = tokenize("test.txt", "[1, 2.5]\n  { :a \"b c\" + }").unwrap();
+        let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind.clone()).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::LBracket,
+            TokenKind::Int(1),
+            TokenKind::Float(2.5),
+            TokenKind::RBracket,
+            TokenKind::LBrace,
+            TokenKind::Symbol("a".to_string()),
+            TokenKind::String("b c".to_string()),
+            TokenKind::Word("+".to_string()),
+            TokenKind::RBrace,
+        ]);
+        assert_eq!((tokens[2].span.line, tokens[2].span.column, tokens[2].span.len), (1, 5, 3));
+        assert_eq!((tokens[6].span.line, tokens[6].span.column), (2, 8));
+        assert_eq!(&*tokens[6].span.file, "test.txt");
+
+        let error = tokenize("test.txt", "[1, {2]}").unwrap_err();
+        assert_eq!((error.kind, error.span.unwrap().column), (ErrorKind::ParseError("Mismatched closing bracket".to_string()), 7));
+        assert_eq!(tokenize("test.txt", "{ [1..3] ").unwrap_err().kind, ErrorKind::ParseError("Missing closing bracket".to_string()));
+    }
+
+    //tests that spacing inside lists and quotations no longer matters
+    #[test]
+    fn spaced_lists_and_quotations() {
+        let mut stack = Stack::new();
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[ 1, 2 ] map { 10 * }"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(10), StackValue::Int(20)])))
+        );
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[ 1 .. 3 ]"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::Int(2), StackValue::Int(3)])))
+        );
+    }
+
+    //tests that programs are parsed into nodes once and quotations keep their contents as written
+    #[test]
+    fn parse_into_ast() {
+        let tokens = tokenize("test.txt", "1 :x if { \"a b\" } { dup } map { 1 + } :=").unwrap();
+        let kinds: Vec<NodeKind> = parse_program(&tokens).unwrap().into_iter().map(|node| node.kind).collect();
+        assert_eq!(kinds[0], NodeKind::Literal(StackValue::Int(1)));
+        assert_eq!(kinds[1], NodeKind::Literal(StackValue::Symbol("x".to_string())));
+        assert!(matches!(&kinds[2], NodeKind::If(t, f) if t.len() == 1 && f.len() == 1));
+        assert!(matches!(&kinds[3], NodeKind::Apply(word, _) if word == "map"));
+        assert_eq!(kinds[4], NodeKind::Assign);
+
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "{ \"a b\" } exec"), Ok(StackValue::String("a b".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "5 { dup * } exec"), Ok(StackValue::Int(25)));
+        assert_eq!(stack.parse("if { 1 }").unwrap_err().kind, ErrorKind::ParseError("Expected a '{' block after 'if'".to_string()));
+    }
+
+    //tests quotations and lists nested inside each other
+    #[test]
+    fn nested_quotations_and_lists() {
+        let mut stack = Stack::new();
+        assert_eq!(
+            execute_and_get_top(&mut stack, "{ [1,2] { 1 + } map } exec"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(2), StackValue::Int(3)])))
+        );
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[ { 1 + } { 10 * } ] { 5 swap exec } map"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(6), StackValue::Int(50)])))
+        );
+        assert_eq!(
+            execute_and_get_top(&mut stack, "{ [[2], [3, 4]] { { length } exec } map } exec"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::Int(2)])))
+        );
+        assert_eq!(execute_and_get_top(&mut stack, "{ { { 7 } } } exec exec exec"), Ok(StackValue::Int(7)));
+        //nesting up to the limit works, deeper nesting is a parse error and not a crash
+        let nested = |open: &str, inner: &str, close: &str, depth: usize| format!("{}{}{}", open.repeat(depth), inner, close.repeat(depth));
+        let too_deep = ErrorKind::ParseError("Quotations and lists are nested too deeply".to_string());
+        assert_eq!(execute_and_get_top(&mut stack, &format!("{} 100 times {{ exec }}", nested("{ ", "7", " }", 100))), Ok(StackValue::Int(7)));
+        assert_eq!(execute_and_get_top(&mut stack, &format!("{} length", nested("[", "1", "]", 100))), Ok(StackValue::Int(1)));
+        assert_eq!(stack.parse(&nested("{ ", "7", " }", 101)).unwrap_err().kind, too_deep);
+        assert_eq!(stack.parse(&nested("{ ", "7", " }", 10000)).unwrap_err().kind, too_deep);
+        assert_eq!(stack.parse(&nested("[", "1", "]", 10000)).unwrap_err().kind, too_deep);
+        assert_eq!(stack.parse(&nested("(", "1", ")", 10000)).unwrap_err().kind, too_deep);
+        assert_eq!(stack.parse(&nested("map { ", "1", " }", 10000)).unwrap_err().kind, too_deep);
+        assert_eq!(stack.parse(&nested("push ", "1", "", 10000)).unwrap_err().kind, too_deep);
+    }
+
+    //tests that programs are compiled to instructions, with if turned into jumps
+    #[test]
+    fn compile_to_bytecode() {
+        let tokens = tokenize("test.txt", "1 x + True if { 2 } { 3 }").unwrap();
+        let chunk = compile(&parse_program(&tokens).unwrap()).unwrap();
+        let add = builtin_index("+").unwrap();
+        assert_eq!(chunk.code, vec![
+            Instr::Push(0),
+            Instr::CallWord(0),
+            Instr::CallBuiltin(add),
+            Instr::Push(1),
+            Instr::JumpIfFalse(7),
+            Instr::Push(2),
+            Instr::Jump(8),
+            Instr::Push(3),
+            Instr::Return,
+        ]);
+        assert_eq!(chunk.names, vec!["x".to_string()]);
+
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "False if { 2 } { 3 }"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 if { 2 } { 3 }"), Err(mismatch("if", "a bool as the condition", "Int(1)")));
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[1..1000] map { 2 * } 0 foldl { + }"),
+            Ok(StackValue::Int(1001000))
+        );
+        //right after a quotation the word uses the ones on the stack and the block after it is just pushed
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] { 1 + } map { length } exec"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "True { 1 } { 2 } if { 10 * } exec"), Ok(StackValue::Int(10)));
+        assert_eq!(execute_and_get_top(&mut stack, "True if { [1] } { [2] } map { 5 + }"), Ok(ints(&[6])));
+        assert_eq!(execute_and_show(&mut stack, "{ { 1 + } map { 2 * } }"), Ok("{{1 +} map {2 *}}".to_string()));
+    }
+
+    //tests defining words with fun and executing quotations bound to a symbol
+    #[test]
+    fn user_defined_words() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "fun square { dup * } 5 square"), Ok(StackValue::Int(25)));
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[1,2,3] map { square }"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::Int(4), StackValue::Int(9)])))
+        );
+        assert_eq!(execute_and_get_top(&mut stack, ":inc { 1 + } := 41 inc"), Ok(StackValue::Int(42)));
+
+        //recursion and mutual recursion, deep enough that it would overflow the rust stack if calls used it
+        stack.parse("fun fact { dup 1 > if { dup 1 - fact * } { } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "10 fact"), Ok(StackValue::Int(3628800)));
+        stack.parse("fun even { dup 0 == if { pop True } { 1 - odd } }").unwrap();
+        stack.parse("fun odd { dup 0 == if { pop False } { 1 - even } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100001 even"), Ok(StackValue::Bool(false)));
+        stack.parse("fun sumto { dup 0 == if { } { dup 1 - sumto + } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "50000 sumto"), Ok(StackValue::Int(1250025000)));
+
+        assert_eq!(stack.parse("fun dup { 1 }").unwrap_err().kind, ErrorKind::RedefineBuiltin("dup".to_string()));
+        assert_eq!(stack.parse("fun { 1 }").unwrap_err().kind, ErrorKind::ParseError("Expected a name after 'fun'".to_string()));
+    }
+
+    //tests that if keeps what the chosen block does and works inside other code blocks
+    #[test]
+    fn if_else_semantics() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top_two(&mut stack, "True if { 1 2 } { 3 }"), Ok((StackValue::Int(2), StackValue::Int(1))));
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "5 False if { 1 + } { 1 - }"), Ok(StackValue::Int(4)));
+        assert_eq!(execute_and_get_top(&mut stack, "5 True { 10 * } { } if"), Ok(StackValue::Int(50)));
+        assert_eq!(execute_and_get_top(&mut stack, "{ 3 2 > if { \"yes\" } { \"no\" } } exec"), Ok(StackValue::String("yes".to_string())));
+        assert_eq!(
+            execute_and_get_top(&mut stack, "[1,2,3,4] map { dup 2 > if { 10 * } { } }"),
+            Ok(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::Int(2), StackValue::Int(30), StackValue::Int(40)])))
+        );
+        assert_eq!(execute_and_get_top(&mut stack, "[1,2,3,4] 0 foldl { dup 2 > if { + } { pop } }"), Ok(StackValue::Int(7)));
+        stack.data.clear();
+        stack.parse("[1,2,3] each { dup 2 == if { pop } { 100 + } }").unwrap();
+        assert_eq!(stack.data, vec![StackValue::Int(101), StackValue::Int(103)]);
+        assert_eq!(stack.parse("1 if { 2 } { 3 }").unwrap_err().kind, mismatch("if", "a bool as the condition", "Int(1)"));
+        assert_eq!(stack.data, vec![StackValue::Int(101), StackValue::Int(103)]);
+    }
+
+    //tests times, loop and while, also with enough iterations that recursion in rust would overflow
+    #[test]
+    fn looping_constructs() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "1 3 { 2 * } times"), Ok(StackValue::Int(8)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 3 times { 3 * }"), Ok(StackValue::Int(27)));
+        assert_eq!(execute_and_get_top(&mut stack, "0 1000000 times { 1 + }"), Ok(StackValue::Int(1000000)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 { dup 100 > } { 2 * } loop"), Ok(StackValue::Int(128)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 loop { dup 100 > } { 3 * }"), Ok(StackValue::Int(243)));
+        assert_eq!(execute_and_get_top(&mut stack, "0 while { dup 500000 < } { 1 + }"), Ok(StackValue::Int(500000)));
+        assert_eq!(execute_and_get_top(&mut stack, "10 { dup 0 > } { 1 - } while"), Ok(StackValue::Int(0)));
+        assert_eq!(execute_and_get_top(&mut stack, "{ 0 5 times { 1 + } } exec"), Ok(StackValue::Int(5)));
+        assert_eq!(stack.parse("1 loop { 5 } { 1 + }").unwrap_err().kind, mismatch("loop", "a bool as the condition", "Int(5)"));
+        assert_eq!(stack.parse("True times { 1 }").unwrap_err().kind, mismatch("times", "an integer", "Bool(true)"));
+    }
+
+    //tests that errors say what went wrong, point at the source and show the words that were called
+    #[test]
+    fn error_reporting() {
+        let mut stack = Stack::new();
+        assert_eq!(stack.parse("pop dup").unwrap_err().kind, ErrorKind::StackUnderflow);
+        assert_eq!(stack.parse("1 \"a\" +").unwrap_err().kind, mismatch("+", "two numbers", "Int(1) and String(\"a\")"));
+        assert_eq!(stack.parse("nothing").unwrap_err().kind, ErrorKind::UnknownWord("nothing".to_string()));
+        assert_eq!(stack.parse("[] head").unwrap_err().kind, ErrorKind::InvalidValue("The list is empty".to_string()));
+        assert!(stack.data.is_empty());
+
+        let error = stack.parse_source("test.txt", "fun f { 1 0 div }\nfun g { f 2 }\n3 g 4").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::DivisionByZero);
+        let span = error.span.clone().unwrap();
+        assert_eq!((span.line, span.column, span.len), (1, 13, 3));
+        let trace: Vec<(&str, usize, usize)> = error.trace.iter().map(|(word, span)| (word.as_str(), span.line, span.column)).collect();
+        assert_eq!(trace, vec![("f", 2, 9), ("g", 3, 3)]);
+        assert_eq!(
+            error.to_string(),
+            "Division by zero\n  --> test.txt:1:13\n  |\n1 | fun f { 1 0 div }\n  |             ^^^\n  in 'f' called at test.txt:2:9\n  in 'g' called at test.txt:3:3"
+        );
+    }
+
+    //tests the api other rust programs use to embed the interpreter
+    #[test]
+    fn embedding_interpreter() {
+        let mut interpreter = crate::Interpreter::new();
+        interpreter.push(StackValue::Int(2));
+        interpreter.eval("3 +").unwrap();
+        assert_eq!(interpreter.stack(), &[StackValue::Int(5)]);
+        assert_eq!(interpreter.pop(), Some(StackValue::Int(5)));
+        assert_eq!(interpreter.pop(), None);
+
+        interpreter.set_symbol("rate", StackValue::Int(10)).unwrap();
+        interpreter.eval("fun twice { 2 * } rate twice :total swap :=").unwrap();
+        assert_eq!(interpreter.get_symbol("total"), Some(&StackValue::Int(20)));
+        assert!(interpreter.stack().is_empty());
+        assert_eq!(interpreter.set_symbol("dup", StackValue::Int(1)).unwrap_err().kind, ErrorKind::RedefineBuiltin("dup".to_string()));
+
+        //a failed eval leaves the stack as it was
+        interpreter.push(StackValue::Int(1));
+        assert_eq!(interpreter.eval("pop dup").unwrap_err().kind, ErrorKind::StackUnderflow);
+        assert_eq!(interpreter.stack(), &[StackValue::Int(1)]);
+    }
+
+    //tests words registered from rust, including their arity check and that they can't be redefined
+    #[test]
+    fn native_words() {
+        let mut interpreter = crate::Interpreter::new();
+        interpreter.register_word("square", 1, |stack| {
+            stack.unary_op(|value| match value {
+                StackValue::Int(x) => Ok(StackValue::Int(x * x)),
+                value => Err(Error::type_mismatch("square", "an integer", &[&value])),
+            })
+        }).unwrap();
+        interpreter.register_word("greedy", 1, |stack| {
+            stack.pop();
+            stack.pop();
+            Ok(())
+        }).unwrap();
+
+        interpreter.eval("[1,2,3] map { square } 4 square").unwrap();
+        assert_eq!(interpreter.pop(), Some(StackValue::Int(16)));
+        assert_eq!(interpreter.pop(), Some(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::Int(4), StackValue::Int(9)]))));
+
+        assert_eq!(interpreter.eval("square").unwrap_err().kind, ErrorKind::StackUnderflow);
+        assert_eq!(interpreter.eval("True square").unwrap_err().kind, mismatch("square", "an integer", "Bool(true)"));
+        assert_eq!(
+            interpreter.eval("1 2 greedy").unwrap_err().kind,
+            ErrorKind::InvalidValue("'greedy' took more than its 1 values from the stack".to_string())
+        );
+        assert_eq!(interpreter.eval(":square 1 :=").unwrap_err().kind, ErrorKind::RedefineBuiltin("square".to_string()));
+        assert!(interpreter.register_word("swap", 2, |_| Ok(())).is_err());
+        assert!(interpreter.stack().is_empty());
+    }
+
+    //tests print and read through an io that isn't the terminal, using the interactive example script
+    #[test]
+    fn scripted_io() {
+        let io = crate::ScriptedIo::new(&["5", "6", "7"]);
+        let mut interpreter = crate::Interpreter::with_io(io.clone());
+        interpreter.eval_source("example.txt", include_str!("example.txt")).unwrap();
+        assert_eq!(io.output(), vec![
+            "Hello welcome to my program",
+            "Input a number and I will multiply it by 10",
+            "50",
+            "Input a number and I will add 10 to it",
+            "16",
+            "Input a number and I will subtract 10 from it",
+            "-3",
+        ]);
+        assert_eq!(io.remaining(), 0);
+        assert_eq!(interpreter.eval("read").unwrap_err().kind, ErrorKind::IoError("There is no more input to read".to_string()));
+
+        let buffer = crate::BufferIo::new();
+        let mut interpreter = crate::Interpreter::with_io(buffer.clone());
+        interpreter.eval("[1,2] each { print } :a print").unwrap();
+        interpreter.io().write_err("oops").unwrap();
+        assert_eq!(buffer.output(), vec!["1", "2", "a"]);
+        assert_eq!(buffer.errors(), vec!["oops"]);
+    }
+
+    //tests that infinite lists only make the values that are taken from them
+    #[test]
+    fn lazy_lists() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] { dup * } map 10 take"), Ok(ints(&[1, 4, 9, 16, 25, 36, 49, 64, 81, 100])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] filter { 2 div 2 * } 3 take"), Err(mismatch("filter", "a bool as the condition", "Int(0)")));
+        assert_eq!(execute_and_get_top(&mut stack, "[0..] filter { dup 3 div 3 * == } map { 1 + } 4 take"), Ok(ints(&[1, 4, 7, 10])));
+        assert_eq!(execute_and_get_top(&mut stack, "[5..] tail tail head"), Ok(StackValue::Int(7)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] filter { 10 > } head"), Ok(StackValue::Int(11)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] empty"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1,2,3,4] filter { 2 > } 1 take"), Ok(ints(&[3])));
+        assert_eq!(execute_and_show(&mut stack, "[1..] map { 2 * }"), Ok("[1..] map {2 *}".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] length"), Err(ErrorKind::InvalidValue("An infinite list has no length".to_string())));
+
+        //each goes on until the quotation stops it with an error
+        stack.data.clear();
+        assert_eq!(stack.parse("0 :n swap := [1..] each { dup n + :n swap := 100 > if { 1 0 div } { } }").unwrap_err().kind, ErrorKind::DivisionByZero);
+        assert_eq!(execute_and_get_top(&mut stack, "n"), Ok(StackValue::Int(5151)));
+        //taking the values first is the way to stop early
+        assert_eq!(execute_and_get_top(&mut stack, "0 [1..] filter { 2 mod 0 == } 3 take each { + }"), Ok(StackValue::Int(12)));
+        //a filter that never matches and each that never fails stop with an error instead of hanging
+        let never_matches = ErrorKind::InvalidValue("filter found no value in a million values of an infinite list".to_string());
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] filter { 0 < } head"), Err(never_matches.clone()));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] filter { 0 < } 1 take"), Err(never_matches));
+        let endless = ErrorKind::InvalidValue("each went through a million values of an infinite list, use take to get the ones it needs".to_string());
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] each { pop }"), Err(endless));
+    }
+
+    //tests that integers are 64 bit and overflowing them is an error instead of a panic
+    #[test]
+    fn integer_overflow() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "3000000000 3 *"), Ok(StackValue::Int(9000000000)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"9223372036854775807\" parseInteger"), Ok(StackValue::Int(i64::MAX)));
+        assert_eq!(execute_and_get_top(&mut stack, "9000000000 3000000000 >"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "9000000000 2 div"), Ok(StackValue::Int(4500000000)));
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "9223372036854775807 1 +"), Err(ErrorKind::Overflow("+".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "-9223372036854775807 2 -"), Err(ErrorKind::Overflow("-".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "4000000000 4000000000 *"), Err(ErrorKind::Overflow("*".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "-9223372036854775807 1 - -1 div"), Err(ErrorKind::Overflow("div".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1 1 times { 9223372036854775807 * }"), Ok(StackValue::Int(i64::MAX)));
+        assert_eq!(stack.parse("2 64 times { 2 * }").unwrap_err().to_string().lines().next(), Some("Integer overflow in '*'"));
+        //a literal too big for an int is an error and not a float
+        let too_large = ErrorKind::ParseError("The integer literal doesn't fit in 64 bits".to_string());
+        assert_eq!(stack.parse("9223372036854775808").unwrap_err().kind, too_large);
+        assert_eq!(stack.parse("99999999999999999999").unwrap_err().kind, too_large);
+        assert_eq!(stack.parse("-9223372036854775809").unwrap_err().kind, too_large);
+        assert_eq!(execute_and_get_top(&mut stack, "-9223372036854775808"), Ok(StackValue::Int(i64::MIN)));
+        assert_eq!(execute_and_get_top(&mut stack, "1e20"), Ok(StackValue::Float(1e20)));
+    }
+
+    //tests that ints and floats can be used together and the words converting between them
+    #[test]
+    fn numeric_promotion() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "1 2.5 +"), Ok(StackValue::Float(3.5)));
+        assert_eq!(execute_and_get_top(&mut stack, "2.5 1 -"), Ok(StackValue::Float(1.5)));
+        assert_eq!(execute_and_get_top(&mut stack, "4 0.5 *"), Ok(StackValue::Float(2.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "3 1.5 /"), Ok(StackValue::Float(2.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "2 2.5 <"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "2.5 3 >"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 1.0 =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 True =="), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 0.0 /"), Err(ErrorKind::DivisionByZero));
+        assert_eq!(execute_and_get_top(&mut stack, "1.5 2 div"), Err(mismatch("div", "two integers", "Float(1.5) and Int(2)")));
+
+        assert_eq!(execute_and_get_top(&mut stack, "3 toFloat"), Ok(StackValue::Float(3.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "-2.7 toInt"), Ok(StackValue::Int(-2)));
+        assert_eq!(execute_and_get_top(&mut stack, "2.5 round"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "-2.5 floor"), Ok(StackValue::Int(-3)));
+        assert_eq!(execute_and_get_top(&mut stack, "2.1 ceil"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "-2.7 truncate"), Ok(StackValue::Int(-2)));
+        assert_eq!(execute_and_get_top(&mut stack, "7 round"), Ok(StackValue::Int(7)));
+        assert_eq!(execute_and_get_top(&mut stack, "1e20 round"), Err(ErrorKind::InvalidValue("100000000000000000000 can't be made into an integer".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "\"a\" toFloat"), Err(mismatch("toFloat", "a number", "String(\"a\")")));
+    }
+
+    //tests exact rationals, their literals, arithmetic, comparisons and conversions
+    #[test]
+    fn rationals() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "2/6r"), Ok(rational(1, 3)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/-2r"), Ok(rational(-1, 2)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 3 *"), Ok(rational(1, 1)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 3 * 1 =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 1/6r +"), Ok(rational(1, 2)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 1/3r -"), Ok(rational(2, 3)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 2/3r /"), Ok(rational(1, 2)));
+        //only dividing a rational stays exact, two ints still give a float
+        assert_eq!(execute_and_get_top(&mut stack, "1 toRational 3 /"), Ok(rational(1, 3)));
+        assert_eq!(execute_and_get_top(&mut stack, "6 2/3r /"), Ok(rational(9, 1)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 4 /"), Ok(StackValue::Float(0.25)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/2r 0.25 +"), Ok(StackValue::Float(0.75)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 0.3 >"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "2/7r 1/3r <"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/2r not"), Ok(rational(-1, 2)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 0 /"), Err(ErrorKind::DivisionByZero));
+        assert_eq!(execute_and_get_top(&mut stack, "9223372036854775807/2r 3 *"), Err(ErrorKind::Overflow("*".to_string())));
+        assert_eq!(stack.parse("1/0r").unwrap_err().kind, ErrorKind::ParseError("A rational can't have a zero denominator".to_string()));
+
+        assert_eq!(execute_and_get_top(&mut stack, "0.375 toRational"), Ok(rational(3, 8)));
+        assert_eq!(execute_and_get_top(&mut stack, "4 toRational"), Ok(rational(4, 1)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/4r toFloat"), Ok(StackValue::Float(0.25)));
+        assert_eq!(execute_and_get_top_two(&mut stack, "6/4r dup numerator swap denominator"), Ok((StackValue::Int(2), StackValue::Int(3))));
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "[-7/2r, -5/2r, 5/2r] map { round }"), Ok(ints(&[-4, -3, 3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[-7/2r, 7/2r] map { floor }"), Ok(ints(&[-4, 3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[-7/2r, 7/2r] map { ceil }"), Ok(ints(&[-3, 4])));
+        assert_eq!(execute_and_get_top(&mut stack, "[-7/2r, 7/2r] map { truncate }"), Ok(ints(&[-3, 3])));
+        assert_eq!(execute_and_show(&mut stack, "1/3r"), Ok("1/3".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "{ 1/3r }"), Ok("{1/3r}".to_string()));
+    }
+
+    //tests the math words on the different kinds of numbers
+    #[test]
+    fn math_words() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "-7 3 mod"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "-7 3 rem"), Ok(StackValue::Int(-1)));
+        assert_eq!(execute_and_get_top(&mut stack, "7.5 -2 mod"), Ok(StackValue::Float(-0.5)));
+        assert_eq!(execute_and_get_top(&mut stack, "-7/2r 1 mod"), Ok(rational(1, 2)));
+        assert_eq!(execute_and_get_top(&mut stack, "5 0 mod"), Err(ErrorKind::DivisionByZero));
+        assert_eq!(execute_and_get_top(&mut stack, "-3 abs"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "-1/2r abs"), Ok(rational(1, 2)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 2.5 min"), Ok(StackValue::Int(1)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 2.5 max"), Ok(StackValue::Float(2.5)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"a\" max"), Err(mismatch("max", "two numbers", "Int(1) and String(\"a\")")));
+        assert_eq!(execute_and_get_top(&mut stack, "2 10 pow"), Ok(StackValue::Int(1024)));
+        assert_eq!(execute_and_get_top(&mut stack, "2 -2 pow"), Ok(rational(1, 4)));
+        assert_eq!(execute_and_get_top(&mut stack, "2/3r 2 pow"), Ok(rational(4, 9)));
+        assert_eq!(execute_and_get_top(&mut stack, "4 0.5 pow"), Ok(StackValue::Float(2.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "2 64 pow"), Err(ErrorKind::Overflow("pow".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "16 sqrt"), Ok(StackValue::Float(4.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "-1 sqrt"), Err(ErrorKind::InvalidValue("Can't take the square root of a negative number".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "0 exp"), Ok(StackValue::Float(1.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "e log"), Ok(StackValue::Float(1.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "0 log"), Err(ErrorKind::InvalidValue("Can only take the logarithm of a positive number".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "pi 2 / sin"), Ok(StackValue::Float(1.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "0 cos 0 tan +"), Ok(StackValue::Float(1.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "12 -18 gcd"), Ok(StackValue::Int(6)));
+        assert_eq!(execute_and_get_top(&mut stack, "4 6 lcm"), Ok(StackValue::Int(12)));
+        assert_eq!(execute_and_get_top(&mut stack, "1.5 6 lcm"), Err(mismatch("lcm", "two integers", "Float(1.5) and Int(6)")));
+        assert_eq!(execute_and_get_top(&mut stack, "2 2.0 <="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "1/3r 0.5 >="), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 2 !="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"a\" \"a\" !="), Ok(StackValue::Bool(false)));
+    }
+
+    //tests the bitwise words and the hex, binary, octal and separated integer literals
+    #[test]
+    fn bitwise_words() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "0xFF"), Ok(StackValue::Int(255)));
+        assert_eq!(execute_and_get_top(&mut stack, "0b1010"), Ok(StackValue::Int(10)));
+        assert_eq!(execute_and_get_top(&mut stack, "0o17"), Ok(StackValue::Int(15)));
+        assert_eq!(execute_and_get_top(&mut stack, "-0x10"), Ok(StackValue::Int(-16)));
+        assert_eq!(execute_and_get_top(&mut stack, "0xFFFF_FFFF_FFFF_FFFF"), Ok(StackValue::Int(-1)));
+        assert_eq!(execute_and_get_top(&mut stack, "0x8000000000000000"), Ok(StackValue::Int(i64::MIN)));
+        assert_eq!(execute_and_get_top(&mut stack, "-0x8000000000000000"), Ok(StackValue::Int(i64::MIN)));
+        assert_eq!(execute_and_get_top(&mut stack, "1_000_000"), Ok(StackValue::Int(1000000)));
+        assert_eq!(execute_and_get_top(&mut stack, "1_000.5"), Ok(StackValue::Float(1000.5)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"0x1F\" parseInteger"), Ok(StackValue::Int(31)));
+        assert_eq!(stack.parse("0xG1").unwrap_err().kind, ErrorKind::UnknownWord("0xG1".to_string()));
+
+        assert_eq!(execute_and_get_top(&mut stack, "0b1100 0b1010 band"), Ok(StackValue::Int(0b1000)));
+        assert_eq!(execute_and_get_top(&mut stack, "0b1100 0b1010 bor"), Ok(StackValue::Int(0b1110)));
+        assert_eq!(execute_and_get_top(&mut stack, "0b1100 0b1010 bxor"), Ok(StackValue::Int(0b0110)));
+        assert_eq!(execute_and_get_top(&mut stack, "0 bnot"), Ok(StackValue::Int(-1)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 62 shl"), Ok(StackValue::Int(1 << 62)));
+        assert_eq!(execute_and_get_top(&mut stack, "-16 2 shr"), Ok(StackValue::Int(-4)));
+        assert_eq!(execute_and_get_top(&mut stack, "0xFF popcount"), Ok(StackValue::Int(8)));
+        assert_eq!(execute_and_get_top(&mut stack, "-1 popcount"), Ok(StackValue::Int(64)));
+        assert_eq!(execute_and_get_top(&mut stack, "1 64 shl"), Err(ErrorKind::InvalidValue("Can't shift by 64 bits, it has to be from 0 to 63".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1.0 1 band"), Err(mismatch("band", "two integers", "Float(1.0) and Int(1)")));
+    }
+
+    //tests the string words, including that they count characters instead of bytes
+    #[test]
+    fn string_words() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "\"ab\" \"cd\" concat"), Ok(string("abcd")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"a,b,,c\" \",\" split"), Ok(strings(&["a", "b", "", "c"])));
+        assert_eq!(execute_and_get_top(&mut stack, "[\"a\" \"b\" \"c\"] \"-\" join"), Ok(string("a-b-c")));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] \"-\" join"), Err(mismatch("join", "a list of strings", "Int(1)")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"héllo wörld\" 1 4 substr"), Ok(string("éllo")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"abc\" 1 10 substr"), Ok(string("bc")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"abc\" 4 1 substr"), Err(ErrorKind::InvalidValue("The start 4 is past the end of the string of length 3".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "\"héllo\" \"llo\" indexOf"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"hello\" \"x\" indexOf"), Ok(StackValue::Int(-1)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"hello\" \"ell\" contains"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[\"a\" \"b\"] \"b\" contains"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] 3 contains"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"hello\" \"he\" startsWith"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"hello\" \"he\" endsWith"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"Straße\" toUpper"), Ok(string("STRASSE")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"ÅB\" toLower"), Ok(string("åb")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"  hi  \" trim"), Ok(string("hi")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"a-b-c\" \"-\" \"+\" replace"), Ok(string("a+b+c")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"añb\" chars"), Ok(strings(&["a", "ñ", "b"])));
+        assert_eq!(execute_and_get_top(&mut stack, "\"añb\" strlen"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "\"ab\" 3 repeat"), Ok(string("ababab")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"ab\" -1 repeat"), Err(ErrorKind::InvalidValue("'repeat' can't take a negative number, got -1".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"a\" concat"), Err(mismatch("concat", "a string", "Int(1)")));
+    }
+
+    //tests escapes, raw strings and triple quoted strings
+    #[test]
+    fn string_literals() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, r#""say \"hi\"\n\tok\\""#), Ok(string("say \"hi\"\n\tok\\")));
+        assert_eq!(execute_and_get_top(&mut stack, r#""\u{48}\u{e9}\u{1F600}""#), Ok(string("Hé😀")));
+        assert_eq!(execute_and_get_top(&mut stack, r#"r"C:\dir\n""#), Ok(string("C:\\dir\\n")));
+        assert_eq!(execute_and_get_top(&mut stack, r###"r#"a "quoted" word"#"###), Ok(string("a \"quoted\" word")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"\"\"\nfirst \"line\"\n  second\\tline\"\"\""), Ok(string("first \"line\"\n  second\tline")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"\" strlen"), Ok(StackValue::Int(0)));
+
+        let error = tokenize("test.txt", "\"ab\\qc\"").unwrap_err();
+        assert_eq!((error.kind, error.span.unwrap().column), (ErrorKind::ParseError("Unknown escape sequence '\\q'".to_string()), 4));
+        let error = tokenize("test.txt", "1 \"\"\"abc\n\"\"").unwrap_err();
+        assert_eq!((error.kind, error.span.unwrap().column), (ErrorKind::ParseError("Unterminated string literal".to_string()), 3));
+        assert!(tokenize("test.txt", "\"\\u{110000}\"").is_err());
+
+        //strings in quotations are shown with their escapes so they can be read back in
+        assert_eq!(execute_and_show(&mut stack, "{ \"a\\\"b\" }"), Ok("{\"a\\\"b\"}".to_string()));
+    }
+
+    //tests filling in format templates with values from the stack
+    #[test]
+    fn format_word() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "3 10 \"Total: {} of {}\" format"), Ok(string("Total: 3 of 10")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"x\" [1 2] True \"{} {} {}\" format"), Ok(string("x [Int(1), Int(2)] true")));
+        assert_eq!(execute_and_get_top(&mut stack, "3.14159 \"{:.2}\" format"), Ok(string("3.14")));
+        assert_eq!(execute_and_get_top(&mut stack, "5 1/3r \"{:.1} {:.3}\" format"), Ok(string("5.0 0.333")));
+        assert_eq!(execute_and_get_top(&mut stack, "42 \"[{:6}]\" format"), Ok(string("[    42]")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"ab\" \"[{:6}]\" format"), Ok(string("[ab    ]")));
+        assert_eq!(execute_and_get_top(&mut stack, "42 \"[{:<6}]\" format"), Ok(string("[42    ]")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"ab\" \"[{:^6}]\" format"), Ok(string("[  ab  ]")));
+        assert_eq!(execute_and_get_top(&mut stack, "-2.5 \"[{:08.2}]\" format"), Ok(string("[-0002.50]")));
+        assert_eq!(execute_and_get_top(&mut stack, "\"{{}} {}\" 1 swap format"), Ok(string("{} 1")));
+
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"{} {}\" format"), Err(ErrorKind::StackUnderflow));
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"{:x}\" format"), Err(ErrorKind::InvalidValue("Invalid format template \"{:x}\": can't read {:x}".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"{\" format"), Err(ErrorKind::InvalidValue("Invalid format template \"{\": a { without a }".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1 \"}\" format"), Err(ErrorKind::InvalidValue("Invalid format template \"}\": a } without a {".to_string())));
+    }
+
+    //tests map literals and the dictionary words
+    #[test]
+    fn map_values() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_show(&mut stack, "{| \"b\" 2 \"a\" 1 |}"), Ok("{| \"a\" 1 \"b\" 2 |}".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "{| |} :x [1 2] put 1 True put"), Ok("{| 1 true :x [Int(1), Int(2)] |}".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "{| \"a\" 1 \"b\" 2 |} \"b\" get"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_show(&mut stack, "{| \"a\" 1 |} \"a\" 5 put"), Ok("{| \"a\" 5 |}".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "{| \"a\" 1 \"b\" 2 |} \"a\" remove"), Ok("{| \"b\" 2 |}".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "{| 1 2 |} 1 has"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| 1 2 |} 2 has"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_show(&mut stack, "{| \"b\" 2 \"a\" 1 |} keys"), Ok("[String(\"a\"), String(\"b\")]".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "{| \"b\" 2 \"a\" 1 |} values"), Ok("[Int(1), Int(2)]".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "{| \"b\" 2 \"a\" 1 |} entries"), Ok("[Vec([String(\"a\"), Int(1)]), Vec([String(\"b\"), Int(2)])]".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "{| 1 2 3 4 |} size"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| \"a\" 1 \"b\" 2 |} {| |} \"b\" 2 put \"a\" 1 put =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| \"a\" 1 |} {| \"a\" 2 |} =="), Ok(StackValue::Bool(false)));
+        //the values are compared like numbers on their own, also inside lists
+        assert_eq!(execute_and_get_top(&mut stack, "{| :a 1 |} {| :a 1.0 |} =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| :a [1 2] |} {| :a [1.0 2.0] |} =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| :a 1 |} {| :b 1.0 |} =="), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "{| :a 1 |} {| :a 1.5 |} !="), Ok(StackValue::Bool(true)));
+        //contains, indexOf and uniq find values the way == does
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] 1.0 contains"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1.0 2.0] 1/2r contains"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "[10 20 30] 20.0 indexOf"), Ok(StackValue::Int(1)));
+        assert_eq!(execute_and_get_top(&mut stack, "[[1 2] [3]] [3.0] indexOf"), Ok(StackValue::Int(1)));
+        assert_eq!(execute_and_get_top(&mut stack, "(1 1.0 2/2r 2) uniq"), Ok(ints(&[1, 2])));
+        assert_eq!(execute_and_show(&mut stack, "{| \"a\" 1 \"b\" 2 |} map { swap pop 10 * }"), Ok("{| \"a\" 10 \"b\" 20 |}".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "0 {| \"a\" 1 \"b\" 2 |} each { swap pop + }"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_show(&mut stack, "{| \"inner\" {| 1 2 |}|}"), Ok("{| \"inner\" {| 1 2 |} |}".to_string()));
+
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "{| \"a\" 1 |} \"b\" get"), Err(ErrorKind::InvalidValue("The key String(\"b\") is not in the map".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "{| |} 1.5 1 put"), Err(mismatch("put", "a bool, integer, string or symbol as the key", "Float(1.5)")));
+        assert_eq!(execute_and_get_top(&mut stack, "[1] size"), Err(mismatch("size", "a map", "Vec([Int(1)])")));
+        assert_eq!(execute_and_get_top(&mut stack, "{| \"a\" 1 \"b\" |}"), Err(ErrorKind::ParseError("A map literal needs a value after every key".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "{| [1] 1 |}"), Err(ErrorKind::ParseError("Map keys have to be bools, integers, strings or symbols".to_string())));
+    }
+
+    //tests tuples that mix types and the element types of nested lists
+    #[test]
+    fn tuples() {
+        let mut stack = Stack::new();
+        let mixed = || Err(ErrorKind::ParseError("Lists with mixed types are not allowed, use a ( ) tuple to mix them".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "(1, \"a\", True)"), Ok(StackValue::Vec(List::from(vec![StackValue::Int(1), StackValue::String("a".to_string()), StackValue::Bool(true)]))));
+        assert_eq!(execute_and_get_top(&mut stack, "()"), Ok(StackValue::Vec(List::from(vec![]))));
+        assert_eq!(execute_and_get_top(&mut stack, "(1 \"a\") tail head"), Ok(StackValue::String("a".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "(1 2) [1 2] =="), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[(1 \"a\") (2 \"b\")] length"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "[[1 2] [] [3]] length"), Ok(StackValue::Int(3)));
+        assert_eq!(execute_and_get_top(&mut stack, "{ (:x 1.5) } exec head"), Ok(StackValue::Symbol("x".to_string())));
+
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "[1 \"a\"]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[[1] [\"a\"]]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[[[1]] [[True]]]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[[1] [] [\"a\"]]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[[] [(1 \"a\")] [(\"b\" 2)]]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[(1 \"a\") (\"b\" 2)]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "[(1 \"a\") (2 \"b\" 3)]"), mixed());
+        assert_eq!(execute_and_get_top(&mut stack, "(1 [2 \"b\"])"), mixed());
+        let error = tokenize("test.txt", "(1 2]").unwrap_err();
+        assert_eq!(error.kind, ErrorKind::ParseError("Mismatched closing bracket".to_string()));
+    }
+
+    //tests the list library words
+    #[test]
+    fn list_library() {
+        let mut stack = Stack::new();
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3 4 5 6] filter { 2 mod 0 == }"), Ok(ints(&[2, 4, 6])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] reverse"), Ok(ints(&[3, 2, 1])));
+        assert_eq!(execute_and_show(&mut stack, "(3 1.5 2 1/2r) sort"), Ok("[Rational(1/2), Float(1.5), Int(2), Int(3)]".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "[\"pear\" \"apple\" \"fig\"] sort"), Ok("[String(\"apple\"), String(\"fig\"), String(\"pear\")]".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "[3 1 2] sortBy { > }"), Ok(ints(&[3, 2, 1])));
+        assert_eq!(execute_and_show(&mut stack, "[\"bb\" \"a\" \"cc\" \"d\"] sortBy { swap strlen swap strlen < }"), Ok("[String(\"a\"), String(\"d\"), String(\"bb\"), String(\"cc\")]".to_string()));
+        assert_eq!(execute_and_show(&mut stack, "[1 2 3] [\"a\" \"b\"] zip"), Ok("[Vec([Int(1), String(\"a\")]), Vec([Int(2), String(\"b\")])]".to_string()));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] [3 4] zip unzip"), Ok(ints(&[3, 4])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] [3 4] zip unzip pop"), Ok(ints(&[1, 2])));
+        assert_eq!(execute_and_get_top(&mut stack, "[10 20 30] 1 nth"), Ok(StackValue::Int(20)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 2 take"), Ok(ints(&[1, 2])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 2 drop"), Ok(ints(&[3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 5 drop"), Ok(ints(&[])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1..] 3 drop 2 take"), Ok(ints(&[4, 5])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3 4 5] 1 3 slice"), Ok(ints(&[2, 3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 2 10 slice"), Ok(ints(&[3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 3 1 slice"), Ok(ints(&[])));
+        assert_eq!(execute_and_get_top(&mut stack, "[[1 2] [] [3]] flatten"), Ok(ints(&[1, 2, 3])));
+        assert_eq!(execute_and_get_top(&mut stack, "0 10 3 range"), Ok(ints(&[0, 3, 6, 9])));
+        assert_eq!(execute_and_get_top(&mut stack, "5 1 -2 range"), Ok(ints(&[5, 3, 1])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3 4] sum"), Ok(StackValue::Int(10)));
+        assert_eq!(execute_and_get_top(&mut stack, "[] sum"), Ok(StackValue::Int(0)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3 4] product"), Ok(StackValue::Int(24)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1.5 2.5] sum"), Ok(StackValue::Float(4.0)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] any { 2 > }"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] all { 2 > }"), Ok(StackValue::Bool(false)));
+        assert_eq!(execute_and_get_top(&mut stack, "[] all { 2 > }"), Ok(StackValue::Bool(true)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 4 6 7] find { 2 mod 0 == }"), Ok(StackValue::Int(4)));
+        assert_eq!(execute_and_get_top(&mut stack, "[10 20 30] 30 indexOf"), Ok(StackValue::Int(2)));
+        assert_eq!(execute_and_get_top(&mut stack, "[10 20 30] 5 indexOf"), Ok(StackValue::Int(-1)));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] [] foldr { cons }"), Ok(ints(&[1, 2, 3])));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] 0 foldr { swap - }"), Ok(StackValue::Int(-6)));
+        assert_eq!(execute_and_get_top(&mut stack, "[3 1 3 2 1] uniq"), Ok(ints(&[3, 1, 2])));
+
+        stack.data.clear();
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] 2 nth"), Err(ErrorKind::InvalidValue("The index 2 is out of range for a list of length 2".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] -1 drop"), Err(ErrorKind::InvalidValue("'drop' can't take a negative number, got -1".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "1 5 0 range"), Err(ErrorKind::InvalidValue("The step of a range can't be 0".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] find { 5 > }"), Err(ErrorKind::InvalidValue("None of the values in the list matched".to_string())));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] sortBy { + }"), Err(mismatch("sortBy", "a bool as the condition", "Int(3)")));
+        assert_eq!(execute_and_get_top(&mut stack, "[[1] [2]] sort"), Err(mismatch("sort", "values that can be ordered", "Vec([Int(2)]) and Vec([Int(1)])")));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] flatten"), Err(mismatch("flatten", "a list of lists", "Int(1)")));
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2] unzip"), Err(mismatch("unzip", "a list of pairs", "Int(1)")));
+        assert_eq!(execute_and_get_top(&mut stack, "[\"a\"] sum"), Err(mismatch("+", "two numbers", "Int(0) and String(\"a\")")));
+        assert_eq!(execute_and_get_top(&mut stack, "5 reverse"), Err(mismatch("reverse", "a list", "Int(5)")));
+    }
+
+    //tests that lists sharing their values don't change each other
+    #[test]
+    fn shared_lists() {
+        let list = int_list(&[1, 2, 3]);
+        let tail = list.tail().unwrap();
+        let first = tail.clone().cons(StackValue::Int(9));
+        let second = tail.clone().cons(StackValue::Int(8));
+        assert_eq!((first, second), (int_list(&[9, 2, 3]), int_list(&[8, 2, 3])));
+        assert_eq!((list.clone(), tail.clone()), (int_list(&[1, 2, 3]), int_list(&[2, 3])));
+        assert_eq!(tail.clone().append(list.clone()), int_list(&[2, 3, 1, 2, 3]));
+        //the lists made from list share its cells instead of copying them
+        assert!(first_shares(&tail.clone().cons(StackValue::Int(9)), &tail));
+        assert!(list.tail().unwrap().ptr_eq(&tail) && list.skip(2).ptr_eq(&tail.tail().unwrap()));
+        assert!(first_shares(&tail.clone().append(list.clone()).skip(1), &list));
+        assert_eq!(list.into_iter().collect::<Vec<StackValue>>(), int_list(&[1, 2, 3]).to_vec());
+        assert_eq!((tail.get(1), tail.get(2)), (Some(&StackValue::Int(3)), None));
+
+        let mut stack = Stack::new();
+        let top_two = |stack: &mut Stack, code: &str| {
+            stack.parse(code).unwrap();
+            match (stack.pop(), stack.pop()) {
+                (Some(StackValue::Vec(top)), Some(StackValue::Vec(below))) => (top, below),
+                other => panic!("expected two lists, got {:?}", other),
+            }
+        };
+        let (tail, list) = top_two(&mut stack, "[1 2 3] dup tail");
+        assert!(list.tail().unwrap().ptr_eq(&tail));
+        let (consed, list) = top_two(&mut stack, "[1 2 3] dup 0 swap cons");
+        assert!(first_shares(&consed, &list));
+        let (dropped, list) = top_two(&mut stack, "[1 2 3] dup 2 drop");
+        assert!(list.skip(2).ptr_eq(&dropped));
+        //a long list is freed one cell at a time
+        drop(List::from(vec![StackValue::Int(0); 1_000_000]));
+
+        assert_eq!(execute_and_get_top(&mut stack, "[1 2 3] dup tail 0 swap cons swap 5 swap cons append"), Ok(ints(&[5, 1, 2, 3, 0, 2, 3])));
+        //walking a long list with dup, head and tail shares the list instead of copying it every step
+        stack.parse("fun sumlist { dup empty if { pop 0 } { dup head swap tail sumlist + } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "1 50000 1 range sumlist"), Ok(StackValue::Int(1250025000)));
+    }
+
+    //tests the stack shuffling words and the combinators
+    #[test]
+    fn shuffle_and_combinators() {
+        let mut stack = Stack::new();
+        let mut run = |code: &str| {
+            stack.data.clear();
+            stack.parse(code).map(|_| stack.data.clone()).map_err(|e| e.kind)
+        };
+        assert_eq!(run("1 2 over"), Ok(int_values(&[1, 2, 1])));
+        assert_eq!(run("1 2 3 rot"), Ok(int_values(&[2, 3, 1])));
+        assert_eq!(run("1 2 3 -rot"), Ok(int_values(&[3, 1, 2])));
+        assert_eq!(run("1 2 nip"), Ok(int_values(&[2])));
+        assert_eq!(run("1 2 tuck"), Ok(int_values(&[2, 1, 2])));
+        assert_eq!(run("1 2 2dup"), Ok(int_values(&[1, 2, 1, 2])));
+        assert_eq!(run("1 2 3 2drop"), Ok(int_values(&[1])));
+        assert_eq!(run("10 20 30 0 pick"), Ok(int_values(&[10, 20, 30, 30])));
+        assert_eq!(run("10 20 30 2 pick"), Ok(int_values(&[10, 20, 30, 10])));
+        assert_eq!(run("10 20 30 2 roll"), Ok(int_values(&[20, 30, 10])));
+        assert_eq!(run("10 20 30 0 roll"), Ok(int_values(&[10, 20, 30])));
+        assert_eq!(run("1 2 depth"), Ok(int_values(&[1, 2, 2])));
+        assert_eq!(run("1 2 clear depth"), Ok(int_values(&[0])));
+        assert_eq!(run("1 2 { 10 + } dip"), Ok(int_values(&[11, 2])));
+        assert_eq!(run("5 { 2 * } keep"), Ok(int_values(&[10, 5])));
+        assert_eq!(run("5 { 1 + } { 1 - } bi"), Ok(int_values(&[6, 4])));
+        assert_eq!(run("5 { 1 + } { 1 - } { 2 * } tri"), Ok(int_values(&[6, 4, 10])));
+        assert_eq!(run("[1 2 3] [{ length } { sum } { head }] cleave"), Ok(int_values(&[3, 6, 1])));
+        assert_eq!(run("2 3 { dup * } bi@"), Ok(int_values(&[4, 9])));
+        //the quotations are taken from the stack so combinators can follow each other
+        assert_eq!(run("7 { 3 mod } keep { 2 * } dip"), Ok(int_values(&[2, 7])));
+
+        assert_eq!(run("1 2 rot"), Err(ErrorKind::StackUnderflow));
+        assert_eq!(run("1 2drop"), Err(ErrorKind::StackUnderflow));
+        assert_eq!(run("1 2 2 pick"), Err(ErrorKind::StackUnderflow));
+        assert_eq!(run("1 -1 roll"), Err(ErrorKind::InvalidValue("'roll' can't take a negative number, got -1".to_string())));
+        assert_eq!(run("1 2 bi"), Err(mismatch("bi", "a quotation", "Int(2)")));
+        assert_eq!(run("1 [1 2] cleave"), Err(mismatch("cleave", "a list of quotations", "Int(1)")));
+        assert_eq!(run("{ 1 } dip"), Err(ErrorKind::StackUnderflow));
+    }
+
+    //tests recursing through quotations run by exec, if and the built-ins, it has to finish or give an error and never crash
+    #[test]
+    fn deep_recursion_through_quotations() {
+        let mut stack = Stack::new();
+        stack.parse("fun f { dup 0 == { } { 1 - f } if }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100000 f"), Ok(StackValue::Int(0)));
+        stack.parse("fun h { dup 0 == if { } { 1 - { h } exec } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100000 h"), Ok(StackValue::Int(0)));
+        stack.parse("fun g { dup 0 == { } { 1 - g 1 + } if }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100000 g"), Ok(StackValue::Int(100000)));
+
+        //map and times run their quotation on the rust stack, so only so many of them can be inside each other
+        stack.parse("fun t { dup 0 == if { } { 1 - 1 times { t } } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100 t"), Ok(StackValue::Int(0)));
+        //the program's own run and 199 runs of times inside it are the most there can be
+        assert_eq!(execute_and_get_top(&mut stack, "199 t"), Ok(StackValue::Int(0)));
+        assert_eq!(execute_and_get_top(&mut stack, "200 t"), Err(ErrorKind::CallStackOverflow));
+        assert_eq!(execute_and_get_top(&mut stack, "100000 t"), Err(ErrorKind::CallStackOverflow));
+        stack.parse("fun m { dup 0 == if { } { 1 - [0] map { pop m } head } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100000 m"), Err(ErrorKind::CallStackOverflow));
+        stack.parse("fun k { dup 0 == if { } { 1 - { k } keep pop } }").unwrap();
+        assert_eq!(execute_and_get_top(&mut stack, "100000 k"), Err(ErrorKind::CallStackOverflow));
+        //the stack is usable again afterwards
+        assert_eq!(execute_and_get_top(&mut stack, "10 t"), Ok(StackValue::Int(0)));
+    }
+}